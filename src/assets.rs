@@ -0,0 +1,248 @@
+use crate::backend::Layout;
+use crate::meta::AssetGlobs;
+use color_eyre::eyre::Result;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+// never worth copying, whatever else is excluded
+const DEFAULT_EXCLUDES: &[&str] = &[
+    ".git",
+    ".DS_Store",
+    "__pycache__",
+    ".exportignore",
+    ".gitignore",
+];
+
+/// files of a ctf folder to copy as assets
+///
+/// that's everything but the markdown and meta.toml, less what `globs` and `.exportignore` files,
+/// and `.gitignore` files if `gitignore` is set, leave out
+pub fn find(ctf_folder: &Path, globs: &AssetGlobs, gitignore: bool) -> Result<Vec<PathBuf>> {
+    Ok(walker(ctf_folder, globs, gitignore)?
+        .build()
+        .filter_map(std::result::Result::ok)
+        .map(|x| x.into_path())
+        .filter(|x| x.is_file() && x.file_name().unwrap() != "meta.toml")
+        .filter(|x| x.extension().is_none_or(|x| x != "md"))
+        .collect())
+}
+
+/// markdown files of a ctf folder, sorted, less what the exclude globs of `globs` and ignore
+/// files leave out, like `find`
+///
+/// folders with their own `.git`, like a handout repository, are skipped too, their readmes
+/// aren't writeups
+pub fn markdown(ctf_folder: &Path, globs: &AssetGlobs, gitignore: bool) -> Result<Vec<PathBuf>> {
+    let excludes = AssetGlobs {
+        include: vec![],
+        exclude: globs.exclude.clone(),
+    };
+    Ok(walker(ctf_folder, &excludes, gitignore)?
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|x| x.depth() == 0 || !x.path().join(".git").exists())
+        .build()
+        .filter_map(std::result::Result::ok)
+        .map(|x| x.into_path())
+        .filter(|x| x.is_file() && x.extension().is_some_and(|x| x == "md"))
+        .collect())
+}
+
+fn walker(ctf_folder: &Path, globs: &AssetGlobs, gitignore: bool) -> Result<WalkBuilder> {
+    let mut overrides = OverrideBuilder::new(ctf_folder);
+    for include in &globs.include {
+        overrides.add(include)?;
+    }
+    // later globs win, so excludes beat includes
+    for exclude in DEFAULT_EXCLUDES
+        .iter()
+        .copied()
+        .chain(globs.exclude.iter().map(String::as_str))
+    {
+        overrides.add(&format!("!{}", exclude))?;
+    }
+    let mut walker = WalkBuilder::new(ctf_folder);
+    walker
+        .overrides(overrides.build()?)
+        .hidden(false)
+        .ignore(false)
+        .git_global(false)
+        .git_ignore(gitignore)
+        .git_exclude(gitignore)
+        .require_git(false)
+        .add_custom_ignore_filename(".exportignore");
+    Ok(walker)
+}
+
+/// what a url in the markdown of a challenge points at
+#[derive(Debug, PartialEq)]
+pub enum Target<'a> {
+    /// not a relative path, like a url with a scheme, an absolute path or a fragment
+    External,
+    /// a file, relative to the ctf folder, and the query and fragment the url had
+    File { path: PathBuf, suffix: &'a str },
+    /// a relative path out of the ctf folder
+    Outside,
+}
+
+/// resolves `url` from the markdown of the challenge at `key`
+pub fn resolve<'a>(key: &str, url: &'a str) -> Target<'a> {
+    let path_end = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(path_end);
+    let has_scheme = path.find(':').is_some_and(|x| !path[..x].contains('/'));
+    if path.is_empty() || path.starts_with('/') || has_scheme {
+        return Target::External;
+    }
+
+    // relative to the folder the markdown is in
+    let mut resolved = Path::new(key).parent().unwrap().to_path_buf();
+    for component in Path::new(&percent_decode(path)).components() {
+        match component {
+            Component::Normal(x) => resolved.push(x),
+            Component::ParentDir if resolved.pop() => {}
+            Component::CurDir => {}
+            _ => return Target::Outside,
+        }
+    }
+    Target::File {
+        path: resolved,
+        suffix,
+    }
+}
+
+/// relative url of the file an obsidian `![[target]]` embed in the markdown of the challenge at
+/// `key` is of, which obsidian looks for next to the markdown and then anywhere in the vault
+pub fn embed_url(ctf_folder: &Path, key: &str, target: &str) -> String {
+    if let Target::File { path, .. } = resolve(key, target) {
+        if ctf_folder.join(path).is_file() {
+            return target.to_string();
+        }
+    }
+    let found = WalkDir::new(ctf_folder)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+        .find(|x| x.file_type().is_file() && x.path().ends_with(target));
+    match found {
+        Some(entry) => {
+            let depth = Path::new(key).components().count() - 1;
+            let path = entry.path().strip_prefix(ctf_folder).unwrap();
+            "../".repeat(depth) + &url(path)[1..]
+        }
+        // left for the asset links to report
+        None => target.to_string(),
+    }
+}
+
+fn percent_decode(path: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        match hex {
+            Some(x) if byte == b'%' => {
+                bytes.push(x);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// where an asset, relative to the ctf folder, is copied to relative to the asset folder of the
+/// ctf
+pub fn output_path(relative: &Path, layout: Layout, slugs: &BTreeMap<String, String>) -> PathBuf {
+    if layout == Layout::Bundle {
+        // a file named after a challenge, like a binary next to its writeup, would be copied onto
+        // the folder of its bundle, so it goes in the bundle instead
+        if let Some(slug) = slugs
+            .iter()
+            .find(|(key, slug)| relative == Path::new(key) || relative == Path::new(slug))
+            .map(|(_, slug)| slug)
+        {
+            return Path::new(slug).join(relative.file_name().unwrap());
+        }
+        // a folder named after a challenge is its page bundle, which is named after the slug
+        if let Some((key, slug)) = slugs.iter().find(|(x, _)| relative.starts_with(x)) {
+            return Path::new(slug).join(relative.strip_prefix(key).unwrap());
+        }
+    }
+    relative.to_path_buf()
+}
+
+/// site url of `path` in the output folder, percent-encoded so it works as a markdown link
+pub fn url(path: &Path) -> String {
+    path.components()
+        .map(|x| {
+            x.as_os_str()
+                .to_string_lossy()
+                .replace('%', "%25")
+                .replace(' ', "%20")
+                .replace('(', "%28")
+                .replace(')', "%29")
+        })
+        .fold(String::new(), |url, x| url + "/" + &x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn targets() {
+        let file = |path: &str, suffix| Target::File {
+            path: PathBuf::from(path),
+            suffix,
+        };
+        assert_eq!(resolve("pwn", "./solve.png"), file("solve.png", ""));
+        assert_eq!(
+            resolve("web/xss", "files/a%20b.py#L3"),
+            file("web/files/a b.py", "#L3")
+        );
+        assert_eq!(resolve("web/xss", "../x.png"), file("x.png", ""));
+        assert_eq!(resolve("pwn", "../x.png"), Target::Outside);
+        for url in [
+            "/x.png",
+            "https://e.com/x.png",
+            "mailto:a@b.c",
+            "#flag",
+            "//e.com",
+        ] {
+            assert_eq!(resolve("pwn", url), Target::External);
+        }
+        assert_eq!(url(Path::new("ctf/a b.png")), "/ctf/a%20b.png");
+    }
+
+    #[test]
+    fn bundle_output_paths() {
+        let slugs = BTreeMap::from([
+            ("babyheap".to_string(), "babyheap".to_string()),
+            ("web/Hard Xss".to_string(), "web/hard-xss".to_string()),
+        ]);
+        let output = |path| output_path(Path::new(path), Layout::Bundle, &slugs);
+        assert_eq!(output("babyheap"), Path::new("babyheap/babyheap"));
+        assert_eq!(
+            output("babyheap/libc.so.6"),
+            Path::new("babyheap/libc.so.6")
+        );
+        assert_eq!(output("babyheap.py"), Path::new("babyheap.py"));
+        assert_eq!(
+            output("web/Hard Xss/a.png"),
+            Path::new("web/hard-xss/a.png")
+        );
+        assert_eq!(output("web/hard-xss"), Path::new("web/hard-xss/hard-xss"));
+        assert_eq!(
+            output_path(Path::new("babyheap"), Layout::Page, &slugs),
+            Path::new("babyheap")
+        );
+    }
+}
@@ -0,0 +1,764 @@
+use crate::assets;
+use crate::markdown::{Callout, CalloutStyle};
+use crate::meta::{CTFMeta, ChallengeInfo, ChallengeMeta, Date};
+use crate::taxonomy::Taxonomies;
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toml::value::Table;
+
+#[derive(Debug, Clone, Copy)]
+pub enum OutputType {
+    Zola,
+    Hugo,
+    Jekyll,
+}
+
+impl OutputType {
+    pub fn backend(self, options: BackendOptions) -> Box<dyn SiteBackend> {
+        match self {
+            OutputType::Zola => Box::new(Zola {
+                authors: options.zola_authors,
+                layout: options.layout,
+            }),
+            OutputType::Hugo => Box::new(Hugo {
+                layout: options.layout,
+            }),
+            OutputType::Jekyll => Box::new(Jekyll),
+        }
+    }
+}
+
+/// backend specific settings, ignored by backends they don't apply to
+pub struct BackendOptions {
+    pub zola_authors: ZolaAuthors,
+    pub layout: Layout,
+}
+
+impl FromStr for OutputType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "zola" => Ok(OutputType::Zola),
+            "hugo" => Ok(OutputType::Hugo),
+            "jekyll" => Ok(OutputType::Jekyll),
+            _ => Err("type should be \"zola\", \"hugo\" or \"jekyll\""),
+        }
+    }
+}
+
+/// how the pages of a ctf are laid out in its folder, for zola and hugo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// `index.md` with a `<challenge>.md` next to it
+    Page,
+    /// `_index.md` with a `<challenge>.md` child page next to it
+    Section,
+    /// `_index.md` with a `<challenge>/index.md` page bundle per challenge, so assets in a
+    /// folder named after the challenge end up next to its page
+    Bundle,
+}
+
+impl FromStr for Layout {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "page" => Ok(Layout::Page),
+            "section" => Ok(Layout::Section),
+            "bundle" => Ok(Layout::Bundle),
+            _ => Err("layout should be \"page\", \"section\" or \"bundle\""),
+        }
+    }
+}
+
+/// what a backend gets to know about a page when rendering its front matter
+#[derive(Serialize)]
+pub struct FrontMatter<'a> {
+    pub title: &'a str,
+    pub date: &'a Date,
+    pub updated: Option<&'a Date>,
+    /// position of the challenge within the ctf, unset for the ctf index page
+    pub weight: Option<usize>,
+    pub taxonomies: &'a Taxonomies,
+    pub authors: &'a [String],
+    pub ctf: &'a CTFMeta,
+    /// unset for the ctf index page
+    pub challenge: Option<&'a ChallengeMeta>,
+    /// last part of the url of the challenge page, unset for the ctf index page
+    pub slug: Option<&'a str>,
+    /// keys copied verbatim into the front matter
+    pub extra: &'a Table,
+}
+
+impl FrontMatter<'_> {
+    /// the challenge info, if there is any worth writing
+    fn info(&self) -> Option<&ChallengeInfo> {
+        self.challenge.map(|x| &x.info).filter(|x| !x.is_empty())
+    }
+}
+
+/// a single ctf folder being exported
+pub struct Section<'a> {
+    /// where the ctf goes in the output folder, `/` separated
+    pub folder: &'a str,
+    pub date: &'a Date,
+}
+
+/// a page listing the ctfs, or folders of ctfs, in a folder of the output
+pub struct ListPage<'a> {
+    pub title: &'a str,
+    /// how the generator should order the list, if it can be told in front matter
+    pub sort_by: Option<&'a str>,
+    pub paginate_by: Option<usize>,
+}
+
+/// a challenge page within a section
+pub struct Page<'a> {
+    /// url of the page within the section, `/` separated like the challenge key
+    pub slug: &'a str,
+    pub date: &'a Date,
+}
+
+/// everything that differs between site generators
+///
+/// the default methods lay a ctf out zola/hugo style as one folder per ctf holding its pages, as
+/// picked by `layout`, and the assets next to them
+pub trait SiteBackend {
+    fn front_matter(&self, page: &FrontMatter) -> Result<String>;
+
+    fn layout(&self) -> Layout {
+        Layout::Page
+    }
+
+    /// folder the index and challenge pages of a ctf are written to, relative to the output folder
+    fn section_path(&self, section: &Section) -> PathBuf {
+        PathBuf::from(section.folder)
+    }
+
+    fn index_file_name(&self, _section: &Section) -> String {
+        match self.layout() {
+            Layout::Page => "index.md".to_string(),
+            Layout::Section | Layout::Bundle => "_index.md".to_string(),
+        }
+    }
+
+    /// may contain `/`, for challenges in subfolders and page bundles
+    fn page_file_name(&self, _section: &Section, page: &Page) -> String {
+        match self.layout() {
+            Layout::Page | Layout::Section => format!("{}.md", page.slug),
+            Layout::Bundle => format!("{}/index.md", page.slug),
+        }
+    }
+
+    /// folder the assets of a ctf are copied to, relative to the output folder
+    fn asset_path(&self, section: &Section) -> PathBuf {
+        self.section_path(section)
+    }
+
+    /// url of a challenge page as used from the ctf index page
+    fn link(&self, section: &Section, page: &Page) -> String {
+        // slugs from challenge keys or names can have spaces, which a markdown link can't
+        assets::url(&Path::new(section.folder).join(page.slug))
+    }
+
+    /// url another writeup links to a page of the export by, `page` is unset for the ctf index page
+    fn reference(&self, section: &Section, page: Option<&Page>) -> String {
+        match page {
+            Some(page) => self.link(section, page),
+            None => assets::url(Path::new(section.folder)),
+        }
+    }
+
+    /// how obsidian callouts are written when not picked on the command line
+    fn callout_style(&self) -> CalloutStyle {
+        CalloutStyle::Quote
+    }
+
+    /// a callout written for `CalloutStyle::Shortcode`
+    fn callout_shortcode(&self, callout: &Callout) -> String {
+        // kramdown, and most other markdown renderers, render markdown inside this
+        format!(
+            "<div class=\"callout callout-{}\" markdown=\"1\">\n**{}**\n\n{}\n</div>",
+            callout.kind,
+            callout.title(),
+            callout.body.trim_end()
+        )
+    }
+
+    /// an obsidian `![[image|300]]` embed of the image at `url` with its size
+    fn sized_image(&self, url: &str, _alt: &str, width: u32, height: Option<u32>) -> String {
+        format!(
+            "<img src=\"{}\" width=\"{}\"{}>",
+            url.replace(' ', "%20"),
+            width,
+            height.map_or(String::new(), |x| format!(" height=\"{}\"", x))
+        )
+    }
+
+    /// an obsidian `==highlight==` of `text`
+    fn highlight(&self, text: &str) -> String {
+        format!("<mark>{}</mark>", text)
+    }
+
+    /// file name and contents of the page listing what's in a folder of ctfs, `None` if the
+    /// generator has no such thing
+    fn list_page(&self, list: &ListPage) -> Result<Option<(String, String)>> {
+        Ok(Some((
+            "_index.md".to_string(),
+            toml_front_matter(
+                &ListFrontMatter { title: list.title },
+                &Taxonomies::new(),
+                &Table::new(),
+            )?,
+        )))
+    }
+}
+
+// path of a page within the output folder, which zola and hugo link to pages by
+fn content_path(backend: &dyn SiteBackend, section: &Section, page: Option<&Page>) -> String {
+    let file_name = match page {
+        Some(page) => backend.page_file_name(section, page),
+        None => backend.index_file_name(section),
+    };
+    backend
+        .section_path(section)
+        .join(file_name)
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Serialize)]
+struct ListFrontMatter<'a> {
+    title: &'a str,
+}
+
+// written as a bare toml datetime so generators see a date, not a string
+fn date_value(date: &Date) -> toml::Value {
+    toml::Value::Datetime(date.datetime().clone())
+}
+
+/// recursively merges `extra` into `value`, with `extra` winning on conflicts
+fn merge_extra(value: &mut toml::Value, extra: &Table) {
+    if let toml::Value::Table(table) = value {
+        for (key, extra_value) in extra {
+            match (table.get_mut(key), extra_value) {
+                (Some(existing @ toml::Value::Table(_)), toml::Value::Table(extra_table)) => {
+                    merge_extra(existing, extra_table)
+                }
+                _ => {
+                    table.insert(key.clone(), extra_value.clone());
+                }
+            }
+        }
+    }
+}
+
+// hugo and jekyll keep taxonomies as top level keys, next to keys of their own that can hold terms
+// too, so those taxonomies are taken out to be merged in with `merge_terms` rather than written
+// as a second key of the same name
+fn split_taxonomies(taxonomies: &Taxonomies, term_keys: &[&str]) -> (Taxonomies, Taxonomies) {
+    taxonomies
+        .clone()
+        .into_iter()
+        .partition(|(name, _)| !term_keys.contains(&name.as_str()))
+}
+
+/// adds `terms` to the terms, or the single term, already under their key in `value`
+fn merge_terms(value: &mut toml::Value, terms: &Taxonomies) -> Result<()> {
+    if let toml::Value::Table(table) = value {
+        for (key, terms) in terms {
+            let mut merged = match table.remove(key) {
+                None => vec![],
+                Some(toml::Value::String(x)) => vec![toml::Value::String(x)],
+                Some(toml::Value::Array(x)) => x,
+                Some(_) => {
+                    return Err(eyre!(
+                        "taxonomy {:?} clashes with a front matter key that can't hold terms",
+                        key
+                    ))
+                }
+            };
+            for term in terms {
+                let term = toml::Value::String(term.clone());
+                if !merged.contains(&term) {
+                    merged.push(term);
+                }
+            }
+            table.insert(key.clone(), toml::Value::Array(merged));
+        }
+    }
+    Ok(())
+}
+
+/// the front matter as a table, with `terms` merged in as by `merge_terms` and then `extra`
+fn front_matter_value(
+    front_matter: &impl Serialize,
+    terms: &Taxonomies,
+    extra: &Table,
+) -> Result<toml::Value> {
+    // toml::Value::try_from mangles datetimes into their private wire format, going through a
+    // string keeps them intact
+    let mut value = toml::to_string(front_matter)
+        .and_then(|x| toml::from_str(&x).map_err(serde::ser::Error::custom))
+        .wrap_err("couldn't write front matter")?;
+    merge_terms(&mut value, terms)?;
+    merge_extra(&mut value, extra);
+    Ok(value)
+}
+
+// serializing a table value puts plain keys before sub-tables, which toml requires
+fn toml_front_matter(
+    front_matter: &impl Serialize,
+    terms: &Taxonomies,
+    extra: &Table,
+) -> Result<String> {
+    let value = front_matter_value(front_matter, terms, extra)?;
+    Ok(format!(
+        "+++\n{}+++\n\n\n",
+        toml::to_string(&value).wrap_err("couldn't write front matter")?
+    ))
+}
+
+fn toml_to_yaml(value: &toml::Value) -> serde_yaml::Value {
+    match value {
+        toml::Value::String(x) => serde_yaml::Value::from(x.as_str()),
+        toml::Value::Integer(x) => serde_yaml::Value::from(*x),
+        toml::Value::Float(x) => serde_yaml::Value::from(*x),
+        toml::Value::Boolean(x) => serde_yaml::Value::from(*x),
+        // yaml timestamps are written the same way as toml datetimes
+        toml::Value::Datetime(x) => serde_yaml::Value::from(x.to_string()),
+        toml::Value::Array(x) => x.iter().map(toml_to_yaml).collect(),
+        toml::Value::Table(x) => serde_yaml::Value::Mapping(
+            x.iter()
+                .map(|(k, v)| (serde_yaml::Value::from(k.as_str()), toml_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn yaml_front_matter(
+    front_matter: &impl Serialize,
+    terms: &Taxonomies,
+    extra: &Table,
+) -> Result<String> {
+    let value = toml_to_yaml(&front_matter_value(front_matter, terms, extra)?);
+    Ok(format!(
+        "---\n{}---\n\n\n",
+        serde_yaml::to_string(&value).wrap_err("couldn't write front matter")?
+    ))
+}
+
+/// where zola pages keep their authors, zola has no field for them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZolaAuthors {
+    /// `[taxonomies] authors`, needs an authors taxonomy in the site config
+    Taxonomy,
+    /// `[extra] authors`, for themes to pick up
+    Extra,
+    Both,
+}
+
+impl FromStr for ZolaAuthors {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "taxonomy" => Ok(ZolaAuthors::Taxonomy),
+            "extra" => Ok(ZolaAuthors::Extra),
+            "both" => Ok(ZolaAuthors::Both),
+            _ => Err("zola authors should be \"taxonomy\", \"extra\" or \"both\""),
+        }
+    }
+}
+
+// zola shortcode arguments have no escapes, but can be quoted with any of these
+fn zola_string(s: &str) -> Option<String> {
+    ['"', '\'', '`']
+        .into_iter()
+        .find(|x| !s.contains(*x))
+        .map(|quote| format!("{}{}{}", quote, s, quote))
+}
+
+// zola refuses front matter keys it doesn't know, anything else has to go under extra
+const ZOLA_PAGE_FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "date",
+    "updated",
+    "weight",
+    "draft",
+    "slug",
+    "path",
+    "aliases",
+    "authors",
+    "in_search_index",
+    "template",
+    "taxonomies",
+    "extra",
+];
+const ZOLA_SECTION_FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "draft",
+    "sort_by",
+    "weight",
+    "template",
+    "page_template",
+    "paginate_by",
+    "paginate_path",
+    "paginate_reversed",
+    "insert_anchor_links",
+    "in_search_index",
+    "render",
+    "redirect_to",
+    "transparent",
+    "aliases",
+    "generate_feeds",
+    "extra",
+];
+
+// `extra` with the keys that aren't one of `fields` moved under its `extra` table
+fn zola_extra(extra: &Table, fields: &[&str]) -> Table {
+    let (mut top_level, rest): (Table, Table) = extra
+        .clone()
+        .into_iter()
+        .partition(|(key, _)| fields.contains(&key.as_str()));
+    if !rest.is_empty() {
+        let extra = top_level
+            .entry("extra".to_string())
+            .or_insert_with(|| toml::Value::Table(Table::new()));
+        merge_extra(extra, &rest);
+    }
+    top_level
+}
+
+pub struct Zola {
+    pub authors: ZolaAuthors,
+    pub layout: Layout,
+}
+
+impl Default for Zola {
+    fn default() -> Self {
+        Zola {
+            authors: ZolaAuthors::Extra,
+            layout: Layout::Page,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ZolaFrontMatter<'a> {
+    title: &'a str,
+    date: toml::Value,
+    updated: Option<toml::Value>,
+    weight: Option<usize>,
+    slug: Option<&'a str>,
+    taxonomies: Taxonomies,
+    extra: Option<ZolaExtra<'a>>,
+}
+
+#[derive(Serialize)]
+struct ZolaExtra<'a> {
+    authors: Option<&'a [String]>,
+    #[serde(flatten)]
+    info: Option<&'a ChallengeInfo>,
+}
+
+// zola sections refuse page keys like date and taxonomies, so they go in extra
+#[derive(Serialize)]
+struct ZolaSectionFrontMatter<'a> {
+    title: &'a str,
+    sort_by: &'a str,
+    extra: ZolaSectionExtra<'a>,
+}
+
+#[derive(Serialize)]
+struct ZolaSectionExtra<'a> {
+    date: toml::Value,
+    updated: Option<toml::Value>,
+    authors: &'a [String],
+    taxonomies: &'a Taxonomies,
+}
+
+#[derive(Serialize)]
+struct ZolaListFrontMatter<'a> {
+    title: &'a str,
+    sort_by: Option<&'a str>,
+    paginate_by: Option<usize>,
+}
+
+impl SiteBackend for Zola {
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    fn callout_shortcode(&self, callout: &Callout) -> String {
+        let title = callout.title();
+        match zola_string(&title) {
+            Some(quoted) => format!(
+                "{{% callout(kind=\"{}\", title={}) %}}\n{}\n{{% end %}}",
+                callout.kind,
+                quoted,
+                callout.body.trim_end()
+            ),
+            // no way to quote it, so it goes at the top of the body
+            None => format!(
+                "{{% callout(kind=\"{}\") %}}\n**{}**\n\n{}\n{{% end %}}",
+                callout.kind,
+                title,
+                callout.body.trim_end()
+            ),
+        }
+    }
+
+    // internal links, which zola checks when building
+    fn reference(&self, section: &Section, page: Option<&Page>) -> String {
+        format!("@/{}", content_path(self, section, page))
+    }
+
+    fn list_page(&self, list: &ListPage) -> Result<Option<(String, String)>> {
+        Ok(Some((
+            "_index.md".to_string(),
+            toml_front_matter(
+                &ZolaListFrontMatter {
+                    title: list.title,
+                    sort_by: list.sort_by,
+                    paginate_by: list.paginate_by,
+                },
+                &Taxonomies::new(),
+                &Table::new(),
+            )?,
+        )))
+    }
+
+    fn front_matter(&self, page: &FrontMatter) -> Result<String> {
+        if page.challenge.is_none() && self.layout != Layout::Page {
+            return toml_front_matter(
+                &ZolaSectionFrontMatter {
+                    title: page.title,
+                    sort_by: "weight",
+                    extra: ZolaSectionExtra {
+                        date: date_value(page.date),
+                        updated: page.updated.map(date_value),
+                        authors: page.authors,
+                        taxonomies: page.taxonomies,
+                    },
+                },
+                &Taxonomies::new(),
+                &zola_extra(page.extra, ZOLA_SECTION_FIELDS),
+            );
+        }
+        let authors = Some(page.authors).filter(|x| !x.is_empty());
+        let (taxonomy_authors, extra_authors) = match self.authors {
+            ZolaAuthors::Taxonomy => (authors, None),
+            ZolaAuthors::Extra => (None, authors),
+            ZolaAuthors::Both => (authors, authors),
+        };
+        let mut taxonomies = page.taxonomies.clone();
+        if let Some(authors) = taxonomy_authors {
+            let terms = taxonomies.entry("authors".to_string()).or_default();
+            for author in authors {
+                if !terms.contains(author) {
+                    terms.push(author.clone());
+                }
+            }
+        }
+        toml_front_matter(
+            &ZolaFrontMatter {
+                title: page.title,
+                date: date_value(page.date),
+                updated: page.updated.map(date_value),
+                weight: page.weight,
+                slug: page.slug,
+                taxonomies,
+                extra: Some(ZolaExtra {
+                    authors: extra_authors,
+                    info: page.info(),
+                })
+                .filter(|x| x.authors.is_some() || x.info.is_some()),
+            },
+            &Taxonomies::new(),
+            &zola_extra(page.extra, ZOLA_PAGE_FIELDS),
+        )
+    }
+}
+
+pub struct Hugo {
+    pub layout: Layout,
+}
+
+impl Default for Hugo {
+    fn default() -> Self {
+        Hugo {
+            layout: Layout::Page,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HugoFrontMatter<'a> {
+    title: &'a str,
+    date: toml::Value,
+    lastmod: Option<toml::Value>,
+    weight: Option<usize>,
+    slug: Option<&'a str>,
+    // hugo taxonomies are top level keys
+    #[serde(flatten)]
+    taxonomies: Taxonomies,
+    authors: &'a [String],
+    layout: Option<&'a str>,
+    #[serde(flatten)]
+    info: Option<&'a ChallengeInfo>,
+}
+
+impl SiteBackend for Hugo {
+    fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    fn callout_style(&self) -> CalloutStyle {
+        CalloutStyle::Alert
+    }
+
+    // goldmark drops raw html by default, so the size goes
+    fn sized_image(&self, url: &str, alt: &str, _width: u32, _height: Option<u32>) -> String {
+        format!("![{}](<{}>)", alt, url)
+    }
+
+    // which goldmark renders as a <mark> itself with its mark extension on
+    fn highlight(&self, text: &str) -> String {
+        format!("=={}==", text)
+    }
+
+    fn callout_shortcode(&self, callout: &Callout) -> String {
+        format!(
+            "{{{{% callout kind={:?} title={:?} %}}}}\n{}\n{{{{% /callout %}}}}",
+            callout.kind,
+            callout.title(),
+            callout.body.trim_end()
+        )
+    }
+
+    fn reference(&self, section: &Section, page: Option<&Page>) -> String {
+        format!(
+            "{{{{< relref \"/{}\" >}}}}",
+            content_path(self, section, page)
+        )
+    }
+
+    fn front_matter(&self, page: &FrontMatter) -> Result<String> {
+        let (taxonomies, terms) =
+            split_taxonomies(page.taxonomies, &["authors", "category", "difficulty"]);
+        toml_front_matter(
+            &HugoFrontMatter {
+                title: page.title,
+                date: date_value(page.date),
+                lastmod: page.updated.map(date_value),
+                weight: page.weight,
+                slug: page.slug,
+                taxonomies,
+                authors: page.authors,
+                // a section's _index.md is a list page, not a post
+                layout: Some("post")
+                    .filter(|_| page.challenge.is_some() || self.layout == Layout::Page),
+                info: page.info(),
+            },
+            &terms,
+            page.extra,
+        )
+    }
+}
+
+/// jekyll wants every page under `_posts` and the assets somewhere it will copy verbatim
+pub struct Jekyll;
+
+impl Jekyll {
+    // jekyll only picks up posts named YYYY-MM-DD-title.md
+    fn post_name(date: &Date, slug: &str) -> String {
+        format!("{}-{}", date.day(), slug)
+    }
+
+    fn index_post_name(section: &Section) -> String {
+        Jekyll::post_name(section.date, &slug::slugify(section.folder))
+    }
+
+    fn challenge_post_name(section: &Section, page: &Page) -> String {
+        Jekyll::post_name(
+            page.date,
+            &slug::slugify(format!("{}-{}", section.folder, page.slug)),
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct JekyllFrontMatter<'a> {
+    layout: &'a str,
+    title: &'a str,
+    date: toml::Value,
+    // read by jekyll-seo-tag and jekyll-last-modified-at
+    last_modified_at: Option<toml::Value>,
+    #[serde(flatten)]
+    taxonomies: Taxonomies,
+    author: &'a [String],
+    #[serde(flatten)]
+    info: Option<&'a ChallengeInfo>,
+}
+
+impl SiteBackend for Jekyll {
+    fn front_matter(&self, page: &FrontMatter) -> Result<String> {
+        let (taxonomies, terms) =
+            split_taxonomies(page.taxonomies, &["author", "category", "difficulty"]);
+        yaml_front_matter(
+            &JekyllFrontMatter {
+                layout: "post",
+                title: page.title,
+                date: date_value(page.date),
+                last_modified_at: page.updated.map(date_value),
+                taxonomies,
+                author: page.authors,
+                info: page.info(),
+            },
+            &terms,
+            page.extra,
+        )
+    }
+
+    fn section_path(&self, _section: &Section) -> PathBuf {
+        PathBuf::from("_posts")
+    }
+
+    fn index_file_name(&self, section: &Section) -> String {
+        Jekyll::index_post_name(section) + ".md"
+    }
+
+    fn page_file_name(&self, section: &Section, page: &Page) -> String {
+        Jekyll::challenge_post_name(section, page) + ".md"
+    }
+
+    fn asset_path(&self, section: &Section) -> PathBuf {
+        PathBuf::from("assets").join(section.folder)
+    }
+
+    fn list_page(&self, _list: &ListPage) -> Result<Option<(String, String)>> {
+        Ok(None)
+    }
+
+    fn link(&self, section: &Section, page: &Page) -> String {
+        format!(
+            "{{% post_url {} %}}",
+            Jekyll::challenge_post_name(section, page)
+        )
+    }
+
+    fn reference(&self, section: &Section, page: Option<&Page>) -> String {
+        match page {
+            Some(page) => self.link(section, page),
+            None => format!("{{% post_url {} %}}", Jekyll::index_post_name(section)),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use color_eyre::eyre::{eyre, Result};
+use std::fmt;
+use std::path::PathBuf;
+
+/// something wrong with the input that doesn't stop the export on its own
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// name of the ctf folder
+    pub ctf: String,
+    pub path: PathBuf,
+    /// 1-based line in `path` the problem is on, if it's on one
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.ctf, self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// collects diagnostics over a run, warning about each one as it comes in
+///
+/// in strict mode the run fails at the end if there were any
+pub struct Diagnostics {
+    strict: bool,
+    reported: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(strict: bool) -> Self {
+        Diagnostics {
+            strict,
+            reported: vec![],
+        }
+    }
+
+    pub fn report(&mut self, ctf: &str, path: impl Into<PathBuf>, message: impl Into<String>) {
+        self.report_at(ctf, path, None, message)
+    }
+
+    pub fn report_at(
+        &mut self,
+        ctf: &str,
+        path: impl Into<PathBuf>,
+        line: Option<usize>,
+        message: impl Into<String>,
+    ) {
+        let diagnostic = Diagnostic {
+            ctf: ctf.to_string(),
+            path: path.into(),
+            line,
+            message: message.into(),
+        };
+        eprintln!("warning: {}", diagnostic);
+        self.reported.push(diagnostic);
+    }
+
+    pub fn finish(self) -> Result<()> {
+        if self.strict && !self.reported.is_empty() {
+            return Err(eyre!(
+                "{} problem(s) found in strict mode:\n{}",
+                self.reported.len(),
+                self.reported
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+        Ok(())
+    }
+}
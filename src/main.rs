@@ -1,31 +1,27 @@
-use color_eyre::eyre::Result;
+mod assets;
+mod backend;
+mod diagnostics;
+mod markdown;
+mod meta;
+mod taxonomy;
+mod template;
+
+use assets::Target;
+use backend::{
+    BackendOptions, FrontMatter, Layout, ListPage, OutputType, Page, Section, SiteBackend,
+    ZolaAuthors,
+};
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use diagnostics::Diagnostics;
+use markdown::CalloutStyle;
+use meta::{AssetGlobs, CTFMeta, ChallengeMeta, SlugPolicy, SortKey};
 use path_dsl::path;
-use regex::Regex;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fmt::format;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use structopt::StructOpt;
-use walkdir::WalkDir;
-
-#[derive(Debug, Clone, Copy)]
-enum OutputType {
-    Zola,
-    Hugo,
-}
-
-impl FromStr for OutputType {
-    type Err = &'static str;
-
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s {
-            "zola" => Ok(OutputType::Zola),
-            "hugo" => Ok(OutputType::Hugo),
-            _ => Err("type should be \"zola\" or \"hugo\""),
-        }
-    }
-}
+use taxonomy::TaxonomyMapping;
+use template::FrontMatterTemplate;
 
 #[derive(Debug, StructOpt)]
 struct Opt {
@@ -36,210 +32,809 @@ struct Opt {
     #[structopt(short = "t", default_value = "zola")]
     r#type: OutputType,
     #[structopt(short = "r")]
-    // matches URLs in markdown [](.*?) and adds prefix to the url
+    // adds prefix to absolute path urls of links, images and html in the markdown, so /x
+    // becomes /prefix/x
     rewrite_url_prefix: Option<String>,
     #[structopt(short = "a")]
     author: Vec<String>,
+    #[structopt(short = "f", long)]
+    // tera template used to render front matter instead of the backend's, overridden per ctf by
+    // front_matter_template in meta.toml
+    front_matter_template: Option<PathBuf>,
+    #[structopt(long, default_value = "extra")]
+    // where zola front matter puts authors: "taxonomy", "extra" or "both"
+    zola_authors: ZolaAuthors,
+    #[structopt(long, default_value = "page")]
+    // how zola and hugo ctf folders are laid out: "page" for index.md with challenge pages next
+    // to it, "section" for _index.md with child pages, or "bundle" for _index.md with a
+    // <challenge>/index.md page bundle per challenge
+    layout: Layout,
+    #[structopt(long = "taxonomy")]
+    // taxonomy=source,source to file pages under, sources are "tags", "ctf-tags", "ctf" and
+    // "category". defaults to tags=tags
+    taxonomies: Vec<TaxonomyMapping>,
+    #[structopt(long)]
+    // put a table of category, points, solves, difficulty and flag above each challenge
+    info_box: bool,
+    #[structopt(long, default_value = "1")]
+    // levels challenge headings are pushed down by on the ctf index page, to sit under the
+    // challenge's own heading
+    demote_headings: usize,
+    #[structopt(long)]
+    // how obsidian callouts are written: "quote", "alert" for github style alerts or "shortcode"
+    // for a callout shortcode. defaults to alert for hugo and quote otherwise
+    callouts: Option<CalloutStyle>,
+    #[structopt(long, default_value = "weight")]
+    // order of challenges on the index page and in weight front matter: "weight", "name",
+    // "category", "points" or "date". ties keep the meta.toml order
+    sort_by: SortKey,
+    #[structopt(long, default_value = "slugify")]
+    // url slug and file name of challenge pages: "slugify" the key, keep the "key" as is, or
+    // slugify the challenge "name". a slug in meta.toml wins over all of them
+    slug: SlugPolicy,
+    #[structopt(long)]
+    // fail instead of warning about problems like challenges missing their markdown
+    strict: bool,
+    #[structopt(long, default_value = "warn")]
+    // what to do with markdown files that aren't a challenge in meta.toml: "warn", "fail", or
+    // "include" them titled by their first heading
+    orphans: OrphanPolicy,
+    #[structopt(long)]
+    // write every ctf straight into the output folder instead of keeping the input's nesting
+    flatten: bool,
+    #[structopt(long)]
+    // write a section index page for every folder the ctfs are nested in
+    section_indexes: bool,
+    #[structopt(long)]
+    // title of a section index page written for the output folder itself
+    root_index: Option<String>,
+    #[structopt(long)]
+    // put each ctf in a folder for the year of its date, with a section index page per year
+    year_sections: bool,
+    #[structopt(long)]
+    // sort_by of section index pages, for zola
+    list_sort_by: Option<String>,
+    #[structopt(long)]
+    // paginate_by of section index pages, for zola
+    paginate_by: Option<usize>,
+    #[structopt(long = "include")]
+    // gitignore style glob of assets to copy, only assets matching one are copied if given. added
+    // to per ctf by assets.include in meta.toml
+    include_assets: Vec<String>,
+    #[structopt(long = "exclude")]
+    // gitignore style glob of assets not to copy, added to per ctf by assets.exclude in
+    // meta.toml and by .exportignore files
+    exclude_assets: Vec<String>,
+    #[structopt(long)]
+    // don't copy assets ignored by .gitignore files
+    gitignore: bool,
+    #[structopt(long)]
+    // only copy assets the markdown links to or embeds, out of those the globs pick
+    referenced_assets: bool,
 }
 
-fn make_front_matter(
-    name: impl AsRef<str>,
-    date: impl AsRef<str>,
-    tags: &[impl AsRef<str>],
-    authors: &[impl AsRef<str>],
-    output_type: OutputType,
-) -> String {
-    match output_type {
-        OutputType::Zola => format!(
-            "+++\ntitle=\"{}\"\ndate = {}\n\n[taxonomies]\ntags = [{}]\n+++\n\n\n",
-            name.as_ref(),
-            date.as_ref(),
-            tags
-                .into_iter()
-                .map(|x| format!("{:?}", x.as_ref()))
-                .collect::<Vec<_>>()
-                .join(","),
-
-        ),
-        OutputType::Hugo => format!(
-            "+++\ntitle=\"{}\"\ndate = {}\ntags = [{}]\nauthors = [{}]\nlayout = \"post\"\n+++\n\n\n",
-            name.as_ref(),
-            date.as_ref(),
-            tags
-                .iter()
-                .map(|x| format!("{:?}", x.as_ref()))
-                .collect::<Vec<_>>()
-                .join(","),
-            authors
-                .iter()
-                .map(|x| format!("{:?}", x.as_ref()))
-                .collect::<Vec<_>>()
-                .join(","),
-        ),
+/// what to do with markdown files in a ctf folder that aren't listed in its meta.toml
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OrphanPolicy {
+    Warn,
+    Fail,
+    Include,
+}
+
+impl FromStr for OrphanPolicy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "warn" => Ok(OrphanPolicy::Warn),
+            "fail" => Ok(OrphanPolicy::Fail),
+            "include" => Ok(OrphanPolicy::Include),
+            _ => Err("orphans should be \"warn\", \"fail\" or \"include\""),
+        }
+    }
+}
+
+/// everything about an export other than where it reads from and writes to
+struct ExportOptions {
+    authors: Vec<String>,
+    rewrite_url_prefix: Option<String>,
+    front_matter_template: Option<PathBuf>,
+    taxonomies: Vec<TaxonomyMapping>,
+    info_box: bool,
+    demote_headings: usize,
+    callouts: Option<CalloutStyle>,
+    sort_by: SortKey,
+    slug: SlugPolicy,
+    strict: bool,
+    orphans: OrphanPolicy,
+    flatten: bool,
+    section_indexes: bool,
+    root_index: Option<String>,
+    year_sections: bool,
+    list_sort_by: Option<String>,
+    paginate_by: Option<usize>,
+    assets: AssetGlobs,
+    gitignore: bool,
+    referenced_assets: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            authors: vec![],
+            rewrite_url_prefix: None,
+            front_matter_template: None,
+            taxonomies: TaxonomyMapping::defaults(),
+            info_box: false,
+            demote_headings: 1,
+            callouts: None,
+            sort_by: SortKey::Weight,
+            slug: SlugPolicy::Slugify,
+            strict: false,
+            orphans: OrphanPolicy::Warn,
+            flatten: false,
+            section_indexes: false,
+            root_index: None,
+            year_sections: false,
+            list_sort_by: None,
+            paginate_by: None,
+            assets: AssetGlobs::default(),
+            gitignore: false,
+            referenced_assets: false,
+        }
     }
 }
 
 fn main() -> Result<()> {
     let opt = Opt::from_args();
+    let options = ExportOptions {
+        authors: opt.author,
+        rewrite_url_prefix: opt.rewrite_url_prefix,
+        front_matter_template: opt.front_matter_template,
+        taxonomies: if opt.taxonomies.is_empty() {
+            TaxonomyMapping::defaults()
+        } else {
+            opt.taxonomies
+        },
+        info_box: opt.info_box,
+        demote_headings: opt.demote_headings,
+        callouts: opt.callouts,
+        sort_by: opt.sort_by,
+        slug: opt.slug,
+        strict: opt.strict,
+        orphans: opt.orphans,
+        flatten: opt.flatten,
+        section_indexes: opt.section_indexes,
+        root_index: opt.root_index,
+        year_sections: opt.year_sections,
+        list_sort_by: opt.list_sort_by,
+        paginate_by: opt.paginate_by,
+        assets: AssetGlobs {
+            include: opt.include_assets,
+            exclude: opt.exclude_assets,
+        },
+        gitignore: opt.gitignore,
+        referenced_assets: opt.referenced_assets,
+    };
     process_input_folder(
         &opt.input_folder,
         &opt.output_folder,
-        opt.r#type,
-        &opt.author,
-        opt.rewrite_url_prefix.as_ref().map(|x| x.as_str()),
+        opt.r#type
+            .backend(BackendOptions {
+                zola_authors: opt.zola_authors,
+                layout: opt.layout,
+            })
+            .as_ref(),
+        &options,
     )
 }
 
+/// ctf folders under `input_folder`, relative to it
+///
+/// a folder with a meta.toml is a ctf, and so is a folder with markdown directly in it if there's
+/// no meta.toml anywhere below it, any other folder is searched for more ctfs
+fn find_ctf_folders(input_folder: &Path) -> Result<Vec<PathBuf>> {
+    fn is_git(name: &std::ffi::OsStr) -> bool {
+        name.to_string_lossy().contains(".git")
+    }
+
+    fn walk(input_folder: &Path, relative: &Path, ctf_folders: &mut Vec<PathBuf>) -> Result<()> {
+        let mut folders = std::fs::read_dir(input_folder.join(relative))?
+            .flatten()
+            .filter(|x| x.file_type().unwrap().is_dir())
+            .filter(|x| !is_git(&x.file_name()))
+            .map(|x| relative.join(x.file_name()))
+            .collect::<Vec<_>>();
+        folders.sort();
+        for folder in folders {
+            let path = input_folder.join(&folder);
+            if path.join("meta.toml").exists() {
+                ctf_folders.push(folder);
+                continue;
+            }
+            // a readme in a folder of ctfs doesn't make it a ctf
+            let meta_below = walkdir::WalkDir::new(&path)
+                .into_iter()
+                .filter_entry(|x| !is_git(x.file_name()))
+                .flatten()
+                .any(|x| x.file_name() == "meta.toml");
+            let has_markdown = std::fs::read_dir(&path)?
+                .flatten()
+                .any(|x| x.path().is_file() && x.path().extension().is_some_and(|e| e == "md"));
+            if has_markdown && !meta_below {
+                ctf_folders.push(folder);
+            } else {
+                walk(input_folder, &folder, ctf_folders)?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut ctf_folders = vec![];
+    walk(input_folder, Path::new(""), &mut ctf_folders)?;
+    Ok(ctf_folders)
+}
+
+/// markdown files in a ctf folder that aren't a challenge in its meta.toml, keyed like a challenge
+fn find_orphans(
+    ctf_folder: &Path,
+    ctf_meta: &CTFMeta,
+    options: &ExportOptions,
+) -> Result<Vec<(String, PathBuf)>> {
+    let globs = options.assets.merge(&ctf_meta.assets);
+    Ok(meta::markdown_files(ctf_folder, &globs, options.gitignore)?
+        .into_iter()
+        .filter(|(key, _)| !ctf_meta.challenges.contains_key(key))
+        .collect())
+}
+
+/// a ctf folder of the input, loaded before any ctf is written so writeups can link to each other
+struct Ctf {
+    /// path relative to the input folder, `/` separated, which writeups refer to the ctf by
+    key: String,
+    folder: PathBuf,
+    /// where the ctf goes in the output, `/` separated so it can be used in urls
+    folder_name: String,
+    meta: CTFMeta,
+    /// challenge key to the slug its page is written under
+    slugs: BTreeMap<String, String>,
+}
+
+/// url and title of the page a `ctf/challenge` reference from one writeup to another is to, the
+/// ctf index page if there's no challenge
+fn resolve_reference<'a>(
+    backend: &dyn SiteBackend,
+    ctfs: &'a [Ctf],
+    reference: &str,
+) -> Option<(String, &'a str)> {
+    let (reference, fragment) = reference.split_at(reference.find('#').unwrap_or(reference.len()));
+    let reference = reference.trim_matches('/');
+    let reference = reference.strip_suffix(".md").unwrap_or(reference);
+    let (ctf, challenge) = ctfs.iter().find_map(|ctf| {
+        if reference == ctf.key {
+            return Some((ctf, None));
+        }
+        let challenge = reference.strip_prefix(&ctf.key)?.strip_prefix('/')?;
+        ctf.meta
+            .challenges
+            .iter()
+            .find(|(key, _)| key.as_str() == challenge || ctf.slugs[*key] == challenge)
+            .map(|x| (ctf, Some(x)))
+    })?;
+
+    let section = Section {
+        folder: &ctf.folder_name,
+        date: &ctf.meta.date,
+    };
+    let page = challenge.map(|(key, cmeta)| Page {
+        slug: &ctf.slugs[key],
+        date: cmeta.date.as_ref().unwrap_or(&ctf.meta.date),
+    });
+    let title = challenge.map_or(&ctf.meta.name, |(_, cmeta)| &cmeta.name);
+    Some((backend.reference(&section, page.as_ref()) + fragment, title))
+}
+
 fn process_input_folder(
     input_folder: &str,
     output_folder: &str,
-    output_type: OutputType,
-    authors: &[impl AsRef<str>],
-    rewrite_url_prefix: Option<&str>,
+    backend: &dyn SiteBackend,
+    options: &ExportOptions,
 ) -> Result<()> {
-    for folder in std::fs::read_dir(input_folder)?
-        .flatten()
-        .filter(|x| x.file_type().unwrap().is_dir())
-        .filter(|x| !x.file_name().to_string_lossy().contains(".git"))
-    {
-        let url_regex = Regex::new(r"\[(.*?)\]\(/(.*?)\)").unwrap();
-        let top_level_header_regex = Regex::new(r"(?m)^#([^#].*?)$").unwrap();
-        let ctf_folder = folder.path();
-
-        let ctf_meta: CTFMeta =
-            toml::from_str(&std::fs::read_to_string(path!(&ctf_folder | "meta.toml"))?)?;
-
-        let challenges = ctf_meta
-            .challenges
-            .iter()
+    let authors = &options.authors;
+    let default_template = options
+        .front_matter_template
+        .as_deref()
+        .map(FrontMatterTemplate::load)
+        .transpose()?;
+    let mut diagnostics = Diagnostics::new(options.strict);
+    let mut output_ctf_folders = vec![];
+    let mut ctfs: Vec<Ctf> = vec![];
+    for relative_folder in find_ctf_folders(Path::new(input_folder))? {
+        let ctf_folder = Path::new(input_folder).join(&relative_folder);
+        let key = relative_folder
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let folder_name = if options.flatten {
+            relative_folder
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        } else {
+            key.clone()
+        };
+
+        let meta_path = path!(&ctf_folder | "meta.toml");
+        let mut ctf_meta: CTFMeta = if meta_path.exists() {
+            toml::from_str(&std::fs::read_to_string(&meta_path)?)
+                .wrap_err_with(|| format!("couldn't parse {}", meta_path.display()))?
+        } else {
+            let inferred = CTFMeta::infer(
+                &ctf_folder,
+                &folder_name,
+                &options.assets,
+                options.gitignore,
+                &mut diagnostics,
+            );
+            match inferred {
+                Ok(Some(ctf_meta)) => ctf_meta,
+                Ok(None) => continue,
+                Err(e) => {
+                    diagnostics.report(&folder_name, &ctf_folder, e);
+                    continue;
+                }
+            }
+        };
+        let folder_name = if options.year_sections {
+            format!("{}/{}", &ctf_meta.date.day()[..4], folder_name)
+        } else {
+            folder_name
+        };
+        if let Some(other) = ctfs.iter().find(|x| x.folder_name == folder_name) {
+            diagnostics.report(
+                &folder_name,
+                &ctf_folder,
+                format!(
+                    "ctfs {:?} and {:?} both go to {:?}, skipping this one",
+                    other.key, key, folder_name
+                ),
+            );
+            continue;
+        }
+        output_ctf_folders.push(folder_name.clone());
+
+        // every markdown file of an inferred ctf is a challenge, unless it was already reported
+        let orphans = if meta_path.exists() {
+            find_orphans(&ctf_folder, &ctf_meta, options)?
+        } else {
+            vec![]
+        };
+        for (key, path) in orphans {
+            match options.orphans {
+                OrphanPolicy::Warn => diagnostics.report(
+                    &folder_name,
+                    path,
+                    "markdown file isn't a challenge in meta.toml",
+                ),
+                OrphanPolicy::Fail => {
+                    return Err(eyre!(
+                        "{}: {}: markdown file isn't a challenge in meta.toml",
+                        folder_name,
+                        path.display()
+                    ))
+                }
+                OrphanPolicy::Include => {
+                    let name = markdown::first_heading(&std::fs::read_to_string(&path)?)
+                        .unwrap_or_else(|| key.clone());
+                    ctf_meta.challenges.insert(
+                        key,
+                        ChallengeMeta {
+                            name,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut slugs = BTreeMap::new();
+        let mut slug_keys = BTreeMap::new();
+        for (key, cmeta) in &ctf_meta.challenges {
+            let slug = options.slug.slug(key, cmeta);
+            if let Some(other) = slug_keys.insert(slug.clone(), key) {
+                diagnostics.report(
+                    &folder_name,
+                    &ctf_folder,
+                    format!(
+                        "challenges {:?} and {:?} both have the slug {:?}",
+                        other, key, slug
+                    ),
+                );
+            }
+            slugs.insert(key.clone(), slug);
+        }
+
+        ctfs.push(Ctf {
+            key,
+            folder: ctf_folder,
+            folder_name,
+            meta: ctf_meta,
+            slugs,
+        });
+    }
+
+    for ctf in &ctfs {
+        let Ctf {
+            key: ctf_key,
+            folder: ctf_folder,
+            folder_name,
+            meta: ctf_meta,
+            slugs,
+        } = ctf;
+        let section = Section {
+            folder: folder_name,
+            date: &ctf_meta.date,
+        };
+        let asset_path = backend.asset_path(&section);
+
+        let challenges = options
+            .sort_by
+            .sort(ctf_meta)
+            .into_iter()
             .map(|(a, b)| ((b, a.clone()), a.clone() + ".md"))
             .map(|(a, b)| (a, path!(&ctf_folder | b)))
-            .flat_map(|(a, b)| Some((a, std::fs::read_to_string(b).ok()?)))
+            .flat_map(|(a, b)| match std::fs::read_to_string(&b) {
+                Ok(content) => Some((a, content)),
+                Err(e) => {
+                    diagnostics.report(
+                        folder_name,
+                        b,
+                        format!("couldn't read markdown for challenge {:?}: {}", a.1, e),
+                    );
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        // assets that may be copied, relative to the ctf folder, worked out before links to them
+        // are rewritten so links to ones that won't be can be reported
+        let globs = options.assets.merge(&ctf_meta.assets);
+        let found_assets = assets::find(ctf_folder, &globs, options.gitignore)?
+            .into_iter()
+            .map(|x| x.strip_prefix(ctf_folder).unwrap().to_path_buf())
+            .collect::<BTreeSet<_>>();
+        // assets the markdown links to, relative to the ctf folder
+        let mut referenced_assets = BTreeSet::new();
+        // points links to other writeups at their pages, and relative links to assets at where
+        // they're copied to, recording which assets are linked. links are relative to the folder
+        // of `key`, and diagnostics only have a line if there's a `line_offset` to add to it
+        let rewrite_links =
+            |content: &str,
+             key: &str,
+             md_path: &Path,
+             line_offset: Option<usize>,
+             diagnostics: &mut Diagnostics,
+             referenced_assets: &mut BTreeSet<PathBuf>| {
+                markdown::rewrite_links(content, |link| {
+                    if let Some(reference) = link.url.strip_prefix("@/") {
+                        let resolved = resolve_reference(backend, &ctfs, reference);
+                        if resolved.is_none() {
+                            diagnostics.report_at(
+                                folder_name,
+                                md_path,
+                                line_offset.map(|x| link.line + x),
+                                format!("broken reference to {:?}", link.url),
+                            );
+                        }
+                        return resolved.map(|(url, _)| url);
+                    }
+                    match assets::resolve(key, link.url) {
+                        Target::External => None,
+                        Target::Outside => {
+                            diagnostics.report_at(
+                                folder_name,
+                                md_path,
+                                line_offset.map(|x| link.line + x),
+                                format!("link to {:?} is outside the ctf folder", link.url),
+                            );
+                            None
+                        }
+                        // links between writeups aren't assets
+                        Target::File { path, .. } if path.extension() == Some("md".as_ref()) => {
+                            None
+                        }
+                        Target::File { path, suffix } => {
+                            if !ctf_folder.join(&path).is_file() {
+                                diagnostics.report_at(
+                                    folder_name,
+                                    md_path,
+                                    line_offset.map(|x| link.line + x),
+                                    format!("link to missing file {:?}", link.url),
+                                );
+                                return None;
+                            }
+                            if !found_assets.contains(&path) {
+                                diagnostics.report_at(
+                                    folder_name,
+                                    md_path,
+                                    line_offset.map(|x| link.line + x),
+                                    format!(
+                                        "link to {:?}, which is excluded from the assets",
+                                        link.url
+                                    ),
+                                );
+                                return None;
+                            }
+                            let output_path = assets::output_path(&path, backend.layout(), slugs);
+                            referenced_assets.insert(path);
+                            Some(assets::url(&asset_path.join(output_path)) + suffix)
+                        }
+                    }
+                })
+            };
+        let challenges = challenges
+            .into_iter()
             // here we apply transformations on challenge files which should be present in both individual and collected pages
-            // 1. if rewrite url prefix is specified, insert into all hrefs
+            // 0. drop any front matter the markdown came with, we write our own, counting the lines
+            // it took up so diagnostics point at the right line
             .map(|(a, content)| {
-                if let Some(prefix) = &rewrite_url_prefix {
-                    (
-                        a,
-                        url_regex
-                            .replace_all(&content, &format!("[$1](/{}$2)", prefix))
-                            .to_string(),
-                    )
-                } else {
-                    (a, content)
-                }
+                let body = meta::split_front_matter(&content).1;
+                let skipped_lines = content[..content.len() - body.len()].matches('\n').count();
+                (a, body.to_string(), skipped_lines)
+            })
+            // 1. point links to other writeups at their pages, and relative links and obsidian
+            // embeds of assets at where they're copied to
+            .map(|((cmeta, name), content, skipped_lines)| {
+                let md_path = ctf_folder.join(name.clone() + ".md");
+                // obsidian embeds of files become plain images for the asset links below
+                let content = markdown::rewrite_wikilinks(&content, |link| {
+                    let path = Path::new(link.target.split('#').next().unwrap());
+                    if !link.embed || path.extension().is_none_or(|x| x == "md") {
+                        return None;
+                    }
+                    let url = assets::embed_url(ctf_folder, &name, link.target);
+                    let size = link.alias.and_then(|x| {
+                        let (width, height) = x.split_once('x').unwrap_or((x, ""));
+                        width
+                            .parse::<u32>()
+                            .ok()
+                            .zip(Some(height.parse::<u32>().ok()))
+                    });
+                    Some(match size {
+                        Some((width, height)) => {
+                            backend.sized_image(&url, &path.to_string_lossy(), width, height)
+                        }
+                        None => format!(
+                            "![{}](<{}>)",
+                            link.alias.unwrap_or(&path.to_string_lossy()),
+                            url
+                        ),
+                    })
+                });
+                let content = rewrite_links(
+                    &content,
+                    &name,
+                    &md_path,
+                    Some(skipped_lines),
+                    &mut diagnostics,
+                    &mut referenced_assets,
+                );
+                let content = markdown::rewrite_wikilinks(&content, |link| {
+                    let (target, heading) =
+                        link.target.split_once('#').unwrap_or((link.target, ""));
+                    // obsidian links to headings by their text, generators by their slug
+                    let fragment = Some(heading)
+                        .filter(|x| !x.is_empty())
+                        .map_or(String::new(), |x| format!("#{}", slug::slugify(x)));
+                    let resolved = if target.is_empty() {
+                        Some((fragment, heading))
+                    } else {
+                        // notes in the same ctf can be linked to by their file name alone, like
+                        // obsidian does
+                        let same_ctf = ctf_meta
+                            .challenges
+                            .keys()
+                            .find(|x| x.rsplit('/').next() == Some(target))
+                            .map_or(target, |x| x.as_str());
+                        resolve_reference(backend, &ctfs, target)
+                            .or_else(|| {
+                                let target = format!("{}/{}", ctf_key, same_ctf);
+                                resolve_reference(backend, &ctfs, &target)
+                            })
+                            .map(|(url, title)| (url + &fragment, title))
+                    };
+                    if resolved.is_none() {
+                        diagnostics.report_at(
+                            folder_name,
+                            &md_path,
+                            Some(link.line + skipped_lines),
+                            format!("broken reference to {:?}", link.target),
+                        );
+                    }
+                    resolved.map(|(url, title)| {
+                        let title = link.alias.unwrap_or(title);
+                        let title = title.replace('[', "\\[").replace(']', "\\]");
+                        format!("[{}]({})", title, url)
+                    })
+                });
+                ((cmeta, name), content)
+            })
+            // 2. turn obsidian callouts and highlights into something the generator understands
+            .map(|(a, content)| {
+                let callout_style = options.callouts.unwrap_or_else(|| backend.callout_style());
+                let content =
+                    markdown::rewrite_callouts(&content, &mut |callout| match callout_style {
+                        CalloutStyle::Quote => callout.quote(),
+                        CalloutStyle::Alert => callout.alert(),
+                        CalloutStyle::Shortcode => backend.callout_shortcode(callout),
+                    });
+                (
+                    a,
+                    markdown::convert_highlights(&content, |x| backend.highlight(x)),
+                )
+            })
+            // 3. if rewrite url prefix is specified, insert into all absolute paths
+            .map(|(a, content)| match &options.rewrite_url_prefix {
+                Some(prefix) => (a, markdown::prefix_links(&content, prefix)),
+                None => (a, content),
+            })
+            // 4. if the info box is enabled, put it above the writeup
+            .map(|((cmeta, name), content)| match cmeta.info.info_box() {
+                Some(info_box) if options.info_box => ((cmeta, name), info_box + "\n" + &content),
+                _ => ((cmeta, name), content),
             })
             .collect::<Vec<_>>();
 
-        let index_page = {
-            let index_front_matter = make_front_matter(
-                &ctf_meta.name,
-                &ctf_meta.date,
-                &vec!["ctf-writeups".to_string()],
-                &authors,
-                output_type,
+        // the description goes on the index page, which links to assets the same way
+        let description = ctf_meta.description.as_ref().map(|desc| {
+            let desc = rewrite_links(
+                desc,
+                "meta.toml",
+                &ctf_folder.join("meta.toml"),
+                None,
+                &mut diagnostics,
+                &mut referenced_assets,
             );
-            let description = ctf_meta.description.map(|desc| desc + "\n<!-- more -->\n");
+            match &options.rewrite_url_prefix {
+                Some(prefix) => markdown::prefix_links(&desc, prefix),
+                None => desc,
+            }
+        });
+
+        let ctf_template = ctf_meta
+            .front_matter_template
+            .as_ref()
+            .map(|x| FrontMatterTemplate::load(&ctf_folder.join(x)))
+            .transpose()?;
+        let render_front_matter =
+            |page: &FrontMatter| match ctf_template.as_ref().or(default_template.as_ref()) {
+                Some(template) => template.render(page),
+                None => backend.front_matter(page),
+            };
+
+        let index_page = {
+            let index_front_matter = render_front_matter(&FrontMatter {
+                title: &ctf_meta.name,
+                date: &ctf_meta.date,
+                updated: None,
+                weight: None,
+                taxonomies: &taxonomy::resolve(&options.taxonomies, ctf_meta, None),
+                authors,
+                ctf: ctf_meta,
+                challenge: None,
+                slug: None,
+                extra: &ctf_meta.extra,
+            })?;
+            let description = description.map(|desc| desc + "\n<!-- more -->\n");
 
             index_front_matter
-                + &description.unwrap_or(String::new())
+                + &description.unwrap_or_default()
                 + &challenges
                     .iter()
                     .map(|((cmeta, name), b)| {
+                        let page = Page {
+                            slug: &slugs[name],
+                            date: cmeta.date.as_ref().unwrap_or(&ctf_meta.date),
+                        };
                         format!(
-                            "# [{}](/{}/{})\n{}",
+                            "# [{}]({})\n{}",
                             cmeta.name,
-                            folder.file_name().to_string_lossy(),
-                            slug::slugify(name),
-                            b.replace("\n#", "\n##")
+                            backend.link(&section, &page),
+                            markdown::demote_headings(b, options.demote_headings)
                         )
                     })
                     .collect::<Vec<_>>()
                     .join("\n")
         };
 
-        let challenge_pages = challenges.into_iter().map(|((cmeta, name), content)| {
-            (
-                (cmeta, name),
-                format!(
-                    "{}{}",
-                    make_front_matter(
-                        &cmeta.name,
-                        &ctf_meta.date,
-                        &cmeta.tags.as_ref().unwrap_or(&vec![]),
-                        authors.as_ref(),
-                        output_type
-                    ),
-                    content
-                ),
-            )
-        });
+        let challenge_pages = challenges
+            .into_iter()
+            .enumerate()
+            .map(|(i, ((cmeta, name), content))| {
+                let front_matter = render_front_matter(&FrontMatter {
+                    title: &cmeta.name,
+                    date: cmeta.date.as_ref().unwrap_or(&ctf_meta.date),
+                    updated: cmeta.updated.as_ref(),
+                    weight: Some(i + 1),
+                    taxonomies: &taxonomy::resolve(&options.taxonomies, ctf_meta, Some(cmeta)),
+                    authors: cmeta.authors.as_deref().unwrap_or(authors),
+                    ctf: ctf_meta,
+                    challenge: Some(cmeta),
+                    slug: slugs[&name].rsplit('/').next(),
+                    extra: &cmeta.extra,
+                })?;
+                Ok(((cmeta, name), front_matter + &content))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        let section_path = {
-            let mut section_path = PathBuf::from_str(output_folder).unwrap();
-            section_path.push(folder.file_name().to_string_lossy().to_string());
-            section_path
-        };
-        std::fs::create_dir(&section_path);
+        let output_folder = PathBuf::from_str(output_folder).unwrap();
+        let section_path = output_folder.join(backend.section_path(&section));
+        let asset_path = output_folder.join(asset_path);
+        std::fs::create_dir_all(&section_path)?;
 
-        std::fs::write(path!(&section_path | "index.md"), index_page)?;
-        for ((_, name), content) in challenge_pages {
-            let chal_md_name = format!("{}.md", name);
-            std::fs::write(path!(&section_path | &chal_md_name), content)?;
+        let index_md_name = backend.index_file_name(&section);
+        std::fs::write(path!(&section_path | &index_md_name), index_page)?;
+        for ((cmeta, name), content) in challenge_pages {
+            let page = Page {
+                slug: &slugs[&name],
+                date: cmeta.date.as_ref().unwrap_or(&ctf_meta.date),
+            };
+            let chal_md_path = section_path.join(backend.page_file_name(&section, &page));
+            std::fs::create_dir_all(chal_md_path.parent().unwrap())?;
+            std::fs::write(chal_md_path, content)?;
         }
 
-        let mut assets: Vec<PathBuf> = {
-            let mut assets = vec![];
-            let mut builder = WalkDir::new(folder.path());
-
-            for entry in builder.into_iter().filter_map(std::result::Result::ok) {
-                let entry_path = entry.path();
-                if entry_path.is_file() && entry_path.file_name().unwrap() != "meta.toml" {
-                    match entry_path.extension() {
-                        Some(e) => match e.to_str() {
-                            Some("md") => continue,
-                            _ => assets.push(entry_path.to_path_buf()),
-                        },
-                        None => assets.push(entry_path.to_path_buf()),
-                    }
-                }
-            }
-            assets
-        };
+        let assets = found_assets
+            .iter()
+            .filter(|x| !options.referenced_assets || referenced_assets.contains(*x));
 
-        for asset in assets {
-            let relative_path = asset.strip_prefix(folder.path()).unwrap();
-            let mut output_path = section_path.clone();
-            output_path.push(relative_path);
+        for relative_path in assets {
+            let mut output_path = asset_path.clone();
+            output_path.push(assets::output_path(relative_path, backend.layout(), slugs));
             std::fs::create_dir_all(output_path.parent().unwrap())?;
-            std::fs::copy(asset, output_path)?;
+            std::fs::copy(ctf_folder.join(relative_path), output_path)?;
         }
     }
-    Ok(())
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CTFMeta {
-    name: String,
-    date: String,
-    description: Option<String>,
-    challenges: HashMap<String, ChallengeMeta>,
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ChallengeMeta {
-    name: String,
-    tags: Option<Vec<String>>,
+    // folders of the output holding ctfs, to title of their list page
+    let mut list_pages = BTreeMap::new();
+    if let Some(title) = &options.root_index {
+        list_pages.insert(PathBuf::new(), title.clone());
+    }
+    if options.section_indexes || options.year_sections {
+        for section in output_ctf_folders
+            .iter()
+            .flat_map(|x| Path::new(x).ancestors().skip(1))
+            .filter(|x| x.parent().is_some())
+        {
+            let title = section.file_name().unwrap().to_string_lossy().to_string();
+            list_pages.insert(section.to_path_buf(), title);
+        }
+    }
+    for (section, title) in list_pages {
+        let list = ListPage {
+            title: &title,
+            sort_by: options.list_sort_by.as_deref(),
+            paginate_by: options.paginate_by,
+        };
+        if let Some((file_name, content)) = backend.list_page(&list)? {
+            let section_path = Path::new(output_folder).join(section);
+            std::fs::create_dir_all(&section_path)?;
+            std::fs::write(section_path.join(file_name), content)?;
+        }
+    }
+    diagnostics.finish()
 }
 
+#[cfg(test)]
 mod test {
     use super::*;
     use temp_dir::TempDir;
+    use walkdir::WalkDir;
 
-    #[test]
-    fn it_works() -> Result<()> {
+    fn make_input_dir() -> Result<TempDir> {
         let input_dir = TempDir::new()?;
-        let output_dir = TempDir::new()?;
         let ctf_dir = {
             let mut dir = input_dir.path().to_path_buf();
             dir.push("ctf-test");
@@ -272,15 +867,24 @@ date = \"2022-01-07\"
 [challenges.example]
 name = \"example\"
 tags = [\"tag 1 lol\"]",
-        );
+        )?;
         std::fs::write(&md_dir, "hi lol")?;
         std::fs::write(&asset_dir, "????")?;
+        Ok(input_dir)
+    }
+
+    #[test]
+    fn it_works() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
         process_input_folder(
             input_dir.path().as_os_str().to_string_lossy().as_ref(),
             output_dir.path().as_os_str().to_string_lossy().as_ref(),
-            OutputType::Zola,
-            &vec!["sky"],
-            None,
+            &backend::Zola::default(),
+            &ExportOptions {
+                authors: vec!["sky".to_string()],
+                ..Default::default()
+            },
         )?;
 
         let ctf_example_output = {
@@ -307,11 +911,16 @@ tags = [\"tag 1 lol\"]",
         assert_eq!(
             ctf_example_output,
             "+++
-title=\"example\"
+title = \"example\"
 date = 2022-01-07
+weight = 1
+slug = \"example\"
 
 [taxonomies]
 tags = [\"tag 1 lol\"]
+
+[extra]
+authors = [\"sky\"]
 +++
 
 
@@ -321,11 +930,14 @@ hi lol"
         assert_eq!(
             ctf_index_output,
             "+++
-title=\"test lol\"
+title = \"test lol\"
 date = 2022-01-07
 
 [taxonomies]
 tags = [\"ctf-writeups\"]
+
+[extra]
+authors = [\"sky\"]
 +++
 
 
@@ -337,4 +949,1316 @@ hi lol"
 
         Ok(())
     }
+
+    #[test]
+    fn jekyll_posts() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Jekyll,
+            &ExportOptions {
+                authors: vec!["sky".to_string()],
+                ..Default::default()
+            },
+        )?;
+
+        let output_path = output_dir.path();
+        let ctf_example_output = std::fs::read_to_string(path!(
+            output_path | "_posts" | "2022-01-07-ctf-test-example.md"
+        ))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "_posts" | "2022-01-07-ctf-test.md"))?;
+        let ctf_asset_output =
+            std::fs::read_to_string(path!(output_path | "assets" | "ctf-test" | "example_asset"))?;
+
+        assert_eq!(
+            ctf_example_output,
+            "---
+layout: post
+title: example
+date: 2022-01-07
+tags:
+- tag 1 lol
+author:
+- sky
+---
+
+
+hi lol"
+        );
+
+        assert_eq!(
+            ctf_index_output,
+            "---
+layout: post
+title: test lol
+date: 2022-01-07
+tags:
+- ctf-writeups
+author:
+- sky
+---
+
+
+# [example]({% post_url 2022-01-07-ctf-test-example %})
+hi lol"
+        );
+
+        assert_eq!(ctf_asset_output, "????");
+
+        Ok(())
+    }
+
+    #[test]
+    fn front_matter_template() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
+        let template_path = input_dir.path().join("front_matter.tera");
+        std::fs::write(
+            &template_path,
+            "+++
+title = \"{{ title }}\"
+date = {{ date }}
+draft = true
+{% if challenge %}weight = 10
+{% endif %}
+[extra]
+toc = true
+ctf = \"{{ ctf.name }}\"
++++
+",
+        )?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                authors: vec!["sky".to_string()],
+                front_matter_template: Some(template_path.clone()),
+                ..Default::default()
+            },
+        )?;
+
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+
+        assert_eq!(
+            ctf_example_output,
+            "+++
+title = \"example\"
+date = 2022-01-07
+draft = true
+weight = 10
+
+[extra]
+toc = true
+ctf = \"test lol\"
++++
+
+
+hi lol"
+        );
+        assert!(ctf_index_output.starts_with(
+            "+++
+title = \"test lol\"
+date = 2022-01-07
+draft = true
+
+[extra]"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn extra_front_matter() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+series = \"ctf writeups\"
+
+[extra]
+cover_image = \"cover.png\"
+
+[challenges]
+[challenges.example]
+name = \"example\"
+tags = [\"tag 1 lol\"]
+math = true",
+        )?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                authors: vec!["sky".to_string()],
+                ..Default::default()
+            },
+        )?;
+
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+
+        assert_eq!(
+            ctf_example_output,
+            "+++
+title = \"example\"
+date = 2022-01-07
+weight = 1
+slug = \"example\"
+
+[taxonomies]
+tags = [\"tag 1 lol\"]
+
+[extra]
+authors = [\"sky\"]
+math = true
++++
+
+
+hi lol"
+        );
+        assert!(ctf_index_output.starts_with(
+            "+++
+title = \"test lol\"
+date = 2022-01-07
+
+[taxonomies]
+tags = [\"ctf-writeups\"]
+
+[extra]
+authors = [\"sky\"]
+cover_image = \"cover.png\"
+series = \"ctf writeups\"
++++"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn challenge_dates_and_authors() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+
+[challenges]
+[challenges.example]
+name = \"example\"
+date = 2022-01-10
+updated = \"2022-02-01T12:00:00Z\"
+authors = [\"not sky\"]",
+        )?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo::default(),
+            &ExportOptions {
+                authors: vec!["sky".to_string()],
+                ..Default::default()
+            },
+        )?;
+
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+
+        assert_eq!(
+            ctf_example_output,
+            "+++
+title = \"example\"
+date = 2022-01-10
+lastmod = 2022-02-01T12:00:00Z
+weight = 1
+slug = \"example\"
+tags = []
+authors = [\"not sky\"]
+layout = \"post\"
++++
+
+
+hi lol"
+        );
+        assert!(ctf_index_output.contains("date = 2022-01-07\n"));
+        assert!(ctf_index_output.contains("authors = [\"sky\"]\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn taxonomy_mapping() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+tags = [\"jeopardy\"]
+
+[challenges]
+[challenges.example]
+name = \"example\"
+category = \"pwn\"
+tags = [\"heap\"]",
+        )?;
+        let options = ExportOptions {
+            taxonomies: vec![
+                "tags=tags".parse().unwrap(),
+                "ctfs=ctf".parse().unwrap(),
+                "categories=category,ctf-tags".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &options,
+        )?;
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+        assert!(ctf_example_output.contains(
+            "[taxonomies]
+tags = [\"heap\"]
+ctfs = [\"test lol\"]
+categories = [\"pwn\", \"jeopardy\"]
+"
+        ));
+        assert!(ctf_index_output.contains(
+            "[taxonomies]
+tags = [\"jeopardy\"]
+ctfs = [\"test lol\"]
+categories = [\"jeopardy\"]
+"
+        ));
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo::default(),
+            &options,
+        )?;
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        assert!(ctf_example_output.contains(
+            "date = 2022-01-07
+weight = 1
+slug = \"example\"
+tags = [\"heap\"]
+ctfs = [\"test lol\"]
+categories = [\"pwn\", \"jeopardy\"]
+authors = []
+"
+        ));
+
+        // taxonomies named like keys the backend sets merge their terms into them
+        let options = ExportOptions {
+            authors: vec!["sky".to_string()],
+            taxonomies: vec![
+                "authors=ctf".parse().unwrap(),
+                "category=category,ctf-tags".parse().unwrap(),
+                "author=ctf".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo::default(),
+            &options,
+        )?;
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        assert!(ctf_example_output.contains("authors = [\"sky\", \"test lol\"]\n"));
+        assert!(ctf_example_output.contains("category = [\"pwn\", \"jeopardy\"]\n"));
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Jekyll,
+            &options,
+        )?;
+        let output_path = output_dir.path();
+        let ctf_example_output = std::fs::read_to_string(path!(
+            output_path | "_posts" | "2022-01-07-ctf-test-example.md"
+        ))?;
+        assert!(ctf_example_output.contains("author:\n- sky\n- test lol\n"));
+        assert!(ctf_example_output.contains("category:\n- pwn\n- jeopardy\n"));
+
+        assert!("layout=ctf".parse::<TaxonomyMapping>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn challenge_info() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+
+[challenges]
+[challenges.example]
+name = \"example\"
+category = \"pwn\"
+points = 500
+solves = 3
+flag = \"flag{a|b}\"",
+        )?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                info_box: true,
+                ..Default::default()
+            },
+        )?;
+
+        let output_path = output_dir.path();
+        let ctf_example_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+
+        assert_eq!(
+            ctf_example_output,
+            "+++
+title = \"example\"
+date = 2022-01-07
+weight = 1
+slug = \"example\"
+
+[taxonomies]
+tags = []
+
+[extra]
+category = \"pwn\"
+points = 500
+solves = 3
+flag = \"flag{a|b}\"
++++
+
+
+| category | points | solves | flag |
+| --- | --- | --- | --- |
+| pwn | 500 | 3 | `flag{a\\|b}` |
+
+hi lol"
+        );
+        assert!(ctf_index_output.ends_with(
+            "# [example](/ctf-test/example)
+| category | points | solves | flag |
+| --- | --- | --- | --- |
+| pwn | 500 | 3 | `flag{a\\|b}` |
+
+hi lol"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_challenge_markdown() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+
+[challenges]
+[challenges.example]
+name = \"example\"
+[challenges.missing]
+name = \"missing\"",
+        )?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        assert!(path!(output_path | "ctf-test" | "example.md").exists());
+        assert!(!path!(output_path | "ctf-test" | "missing.md").exists());
+
+        let output_dir = TempDir::new()?;
+        let err = process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("ctf-test"));
+        assert!(err.to_string().contains("missing.md"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn orphaned_markdown() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/forgotten.md"),
+            "some intro\n# Forgotten Chal\nwriteup",
+        )?;
+
+        let output_dir = TempDir::new()?;
+        let err = process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                orphans: OrphanPolicy::Fail,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("forgotten.md"));
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                orphans: OrphanPolicy::Include,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let forgotten_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "forgotten.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+        assert!(forgotten_output.starts_with("+++\ntitle = \"Forgotten Chal\"\n"));
+        assert!(ctf_index_output.contains("# [Forgotten Chal](/ctf-test/forgotten)\n"));
+
+        // markdown in git metadata and handout repositories isn't a forgotten writeup
+        std::fs::remove_file(input_dir.path().join("ctf-test/forgotten.md"))?;
+        let ctf_dir = input_dir.path().join("ctf-test");
+        std::fs::create_dir_all(ctf_dir.join(".git"))?;
+        std::fs::create_dir_all(ctf_dir.join("handout/.git"))?;
+        std::fs::write(ctf_dir.join(".git/notes.md"), "")?;
+        std::fs::write(ctf_dir.join("handout/README.md"), "")?;
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                orphans: OrphanPolicy::Fail,
+                strict: true,
+                ..Default::default()
+            },
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn inferred_meta() -> Result<()> {
+        let input_dir = TempDir::new()?;
+        let ctf_dir = input_dir.path().join("notes-ctf");
+        std::fs::create_dir_all(&ctf_dir)?;
+        std::fs::write(
+            ctf_dir.join("baby-heap.md"),
+            "---
+title: Baby Heap
+date: 2022-02-05
+tags: [heap]
+category: pwn
+---
+# Baby Heap
+tcache go brr",
+        )?;
+        std::fs::write(
+            ctf_dir.join("warmup.md"),
+            "---
+date: 2022-02-04
+aliases:
+tags: misc, easy
+---
+# Sanity Check
+flag in discord",
+        )?;
+        std::fs::create_dir_all(input_dir.path().join("not-a-ctf"))?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let heap_output =
+            std::fs::read_to_string(path!(output_path | "notes-ctf" | "baby-heap.md"))?;
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "notes-ctf" | "index.md"))?;
+
+        assert_eq!(
+            heap_output,
+            "+++
+title = \"Baby Heap\"
+date = 2022-02-05
+weight = 1
+slug = \"baby-heap\"
+
+[taxonomies]
+tags = [\"heap\"]
+
+[extra]
+category = \"pwn\"
++++
+
+
+# Baby Heap
+tcache go brr"
+        );
+        assert!(ctf_index_output.starts_with("+++\ntitle = \"notes-ctf\"\ndate = 2022-02-04\n"));
+        assert!(ctf_index_output.contains("# [Sanity Check](/notes-ctf/warmup)\n"));
+        assert!(!path!(output_path | "not-a-ctf").exists());
+        let warmup_output =
+            std::fs::read_to_string(path!(output_path | "notes-ctf" | "warmup.md"))?;
+        assert!(warmup_output.contains("tags = [\"misc\", \"easy\"]\n"));
+        assert!(!warmup_output.contains("aliases"));
+
+        // a file that doesn't describe a challenge only loses that challenge
+        std::fs::write(ctf_dir.join("broken.md"), "---\ndate: soon\n---\n")?;
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        assert!(path!(output_path | "notes-ctf" | "warmup.md").exists());
+        assert!(!path!(output_path | "notes-ctf" | "broken.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn inferred_meta_skips_handouts() -> Result<()> {
+        let input_dir = TempDir::new()?;
+        let ctf_dir = input_dir.path().join("notes");
+        std::fs::create_dir_all(ctf_dir.join("handout/.git"))?;
+        std::fs::create_dir_all(ctf_dir.join("drafts"))?;
+        std::fs::write(
+            ctf_dir.join("chal.md"),
+            "---\ndate: 2022-02-05\n---\n# Chal\n",
+        )?;
+        std::fs::write(ctf_dir.join("handout/README.md"), "# Handout\n")?;
+        std::fs::write(ctf_dir.join("handout/.git/x.md"), "# X\n")?;
+        std::fs::write(ctf_dir.join("handout/chal.bin"), "")?;
+        std::fs::write(ctf_dir.join("drafts/todo.md"), "# Todo\n")?;
+        std::fs::write(ctf_dir.join(".exportignore"), "drafts/\n")?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let index_output = std::fs::read_to_string(path!(output_path | "notes" | "index.md"))?;
+        assert!(index_output.contains("# [Chal](/notes/chal)\n"));
+        assert!(!index_output.contains("Handout"));
+        assert!(!index_output.contains("# [X]"));
+        assert!(!index_output.contains("Todo"));
+        // the rest of the handout is still copied as assets
+        assert!(path!(output_path | "notes" | "handout" | "chal.bin").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_ctf_folders() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::create_dir_all(input_dir.path().join("2022/jeopardy"))?;
+        std::fs::rename(
+            input_dir.path().join("ctf-test"),
+            input_dir.path().join("2022/jeopardy/ctf-test"),
+        )?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                section_indexes: true,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let ctf_index_output = std::fs::read_to_string(path!(
+            output_path | "2022" | "jeopardy" | "ctf-test" | "index.md"
+        ))?;
+        assert!(ctf_index_output.contains("# [example](/2022/jeopardy/ctf-test/example)\n"));
+        assert!(path!(output_path | "2022" | "jeopardy" | "ctf-test" | "example_asset").exists());
+        assert_eq!(
+            std::fs::read_to_string(path!(output_path | "2022" | "_index.md"))?,
+            "+++\ntitle = \"2022\"\n+++\n\n\n"
+        );
+        assert!(path!(output_path | "2022" | "jeopardy" | "_index.md").exists());
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                flatten: true,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+        assert!(ctf_index_output.contains("# [example](/ctf-test/example)\n"));
+
+        // a readme next to ctfs doesn't hide them, and ctfs flattened onto the same folder aren't
+        // written over each other
+        std::fs::write(input_dir.path().join("2022/README.md"), "# 2022\n")?;
+        std::fs::create_dir_all(input_dir.path().join("2022/ctf-test"))?;
+        for file in ["meta.toml", "example.md", "example_asset"] {
+            std::fs::copy(
+                input_dir.path().join("2022/jeopardy/ctf-test").join(file),
+                input_dir.path().join("2022/ctf-test").join(file),
+            )?;
+        }
+        let output_dir = TempDir::new()?;
+        let result = process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                flatten: true,
+                strict: true,
+                ..Default::default()
+            },
+        );
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains(
+            "ctfs \"2022/ctf-test\" and \"2022/jeopardy/ctf-test\" both go to \"ctf-test\""
+        ));
+        let output_path = output_dir.path();
+        assert!(path!(output_path | "ctf-test" | "index.md").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn root_and_year_sections() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                root_index: Some("CTF Writeups".to_string()),
+                year_sections: true,
+                list_sort_by: Some("date".to_string()),
+                paginate_by: Some(10),
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        assert_eq!(
+            std::fs::read_to_string(path!(output_path | "_index.md"))?,
+            "+++
+title = \"CTF Writeups\"
+sort_by = \"date\"
+paginate_by = 10
++++
+
+
+"
+        );
+        assert!(
+            std::fs::read_to_string(path!(output_path | "2022" | "_index.md"))?
+                .starts_with("+++\ntitle = \"2022\"\n")
+        );
+        let ctf_index_output =
+            std::fs::read_to_string(path!(output_path | "2022" | "ctf-test" | "index.md"))?;
+        assert!(ctf_index_output.contains("# [example](/2022/ctf-test/example)\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn section_and_bundle_layouts() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::create_dir_all(input_dir.path().join("ctf-test/example"))?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/example/solve.py"),
+            "print(1)",
+        )?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola {
+                layout: Layout::Section,
+                ..Default::default()
+            },
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        let index_output = std::fs::read_to_string(path!(output_path | "ctf-test" | "_index.md"))?;
+        assert!(index_output.starts_with(
+            "+++
+title = \"test lol\"
+sort_by = \"weight\"
+
+[extra]
+date = 2022-01-07
+"
+        ));
+        assert!(index_output.contains("# [example](/ctf-test/example)\n"));
+        assert!(path!(output_path | "ctf-test" | "example.md").exists());
+        assert!(!path!(output_path | "ctf-test" | "index.md").exists());
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo {
+                layout: Layout::Bundle,
+            },
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        let index_output = std::fs::read_to_string(path!(output_path | "ctf-test" | "_index.md"))?;
+        assert!(!index_output.contains("layout"));
+        let chal_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example" | "index.md"))?;
+        assert!(chal_output.contains("layout = \"post\"\n"));
+        assert!(chal_output.ends_with("hi lol"));
+        assert_eq!(
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example" | "solve.py"))?,
+            "print(1)"
+        );
+        assert!(path!(output_path | "ctf-test" | "example_asset").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn slug_policy() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+
+[challenges.\"Hard Pwn\"]
+name = \"Hard Pwn\"
+
+[challenges.example]
+name = \"example\"
+slug = \"custom\"",
+        )?;
+        std::fs::write(input_dir.path().join("ctf-test/Hard Pwn.md"), "pwned")?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        let index_output = std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+        assert!(index_output.contains("# [Hard Pwn](/ctf-test/hard-pwn)\n"));
+        assert!(index_output.contains("# [example](/ctf-test/custom)\n"));
+        assert!(
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "hard-pwn.md"))?
+                .contains("slug = \"hard-pwn\"\n")
+        );
+        assert!(
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "custom.md"))?
+                .contains("slug = \"custom\"\n")
+        );
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo::default(),
+            &ExportOptions {
+                slug: SlugPolicy::Key,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let index_output = std::fs::read_to_string(path!(output_path | "ctf-test" | "index.md"))?;
+        assert!(index_output.contains("# [Hard Pwn](/ctf-test/Hard%20Pwn)\n"));
+        assert!(path!(output_path | "ctf-test" | "Hard Pwn.md").exists());
+        assert!(path!(output_path | "ctf-test" | "custom.md").exists());
+
+        std::fs::create_dir_all(input_dir.path().join("ctf-test/Hard Pwn"))?;
+        std::fs::write(input_dir.path().join("ctf-test/Hard Pwn/solve.py"), "")?;
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola {
+                layout: Layout::Bundle,
+                ..Default::default()
+            },
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        assert!(path!(output_path | "ctf-test" | "hard-pwn" | "index.md").exists());
+        assert!(path!(output_path | "ctf-test" | "hard-pwn" | "solve.py").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn relative_asset_links() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/example.md"),
+            "![](./example_asset)\n\n<img src=\"example/solve%20it.png\">\n\n[gone](missing.py)",
+        )?;
+        std::fs::create_dir_all(input_dir.path().join("ctf-test/example"))?;
+        std::fs::write(input_dir.path().join("ctf-test/example/solve it.png"), "")?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+description = \"![cover](cover.png) [old](gone.png)\"
+
+[challenges.example]
+name = \"example\"",
+        )?;
+        std::fs::write(input_dir.path().join("ctf-test/cover.png"), "")?;
+
+        let output_dir = TempDir::new()?;
+        let result = process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola {
+                layout: Layout::Bundle,
+                ..Default::default()
+            },
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        );
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("example.md:5: link to missing file \"missing.py\""));
+        assert!(error.contains("meta.toml: link to missing file \"gone.png\""));
+        let output_path = output_dir.path();
+        let chal_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "example" | "index.md"))?;
+        assert!(chal_output.contains("![](/ctf-test/example_asset)\n"));
+        assert!(chal_output.contains("<img src=\"/ctf-test/example/solve%20it.png\">"));
+        assert!(chal_output.contains("[gone](missing.py)"));
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Jekyll,
+            &ExportOptions {
+                rewrite_url_prefix: Some("blog".to_string()),
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let chal_output = std::fs::read_to_string(path!(
+            output_path | "_posts" | "2022-01-07-ctf-test-example.md"
+        ))?;
+        assert!(chal_output.contains("![](/blog/assets/ctf-test/example_asset)\n"));
+        let index_output =
+            std::fs::read_to_string(path!(output_path | "_posts" | "2022-01-07-ctf-test.md"))?;
+        assert!(index_output.contains("![cover](/blog/assets/ctf-test/cover.png) [old](gone.png)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn cross_writeup_references() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::create_dir_all(input_dir.path().join("other-ctf"))?;
+        std::fs::write(
+            input_dir.path().join("other-ctf/Baby Heap.md"),
+            "---\ndate: 2022-02-05\n---\nback to [the example](@/ctf-test/example#solve) of \
+             [[ctf-test]]\n\n[[nope/x]]",
+        )?;
+
+        let output_dir = TempDir::new()?;
+        let result = process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        );
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("Baby Heap.md:6: broken reference to \"nope/x\""));
+        let output_path = output_dir.path();
+        let heap_output =
+            std::fs::read_to_string(path!(output_path | "other-ctf" | "baby-heap.md"))?;
+        assert!(heap_output.contains(
+            "back to [the example](@/ctf-test/example.md#solve) of [test lol](@/ctf-test/index.md)\n\n[[nope/x]]"
+        ));
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo {
+                layout: Layout::Bundle,
+            },
+            &ExportOptions::default(),
+        )?;
+        let output_path = output_dir.path();
+        let heap_output =
+            std::fs::read_to_string(path!(output_path | "other-ctf" | "baby-heap" | "index.md"))?;
+        assert!(heap_output.contains(
+            "[the example]({{< relref \"/ctf-test/example/index.md\" >}}#solve) of [test lol]({{< relref \"/ctf-test/_index.md\" >}})"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn obsidian_notes() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+
+[challenges.example]
+name = \"example\"
+
+[challenges.\"pwn/heap\"]
+name = \"Heap\"",
+        )?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/example.md"),
+            "![[solve.png]] ![[solve.png|300]]\n\nsee [[heap|the heap one]] [[#Step 2]]\n\n\
+             > [!warning] ==careful==\n> it segfaults",
+        )?;
+        std::fs::create_dir_all(input_dir.path().join("ctf-test/pwn/files"))?;
+        std::fs::write(input_dir.path().join("ctf-test/pwn/files/solve.png"), "")?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/pwn/heap.md"),
+            "![[solve.png]]",
+        )?;
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Hugo::default(),
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let chal_output = std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        assert!(chal_output.contains(
+            "![solve.png](</ctf-test/pwn/files/solve.png>) \
+             ![solve.png](</ctf-test/pwn/files/solve.png>)\n\n\
+             see [the heap one]({{< relref \"/ctf-test/pwn/heap.md\" >}}) [Step 2](#step-2)\n\n\
+             > [!WARNING]\n> **==careful==**\n>\n> it segfaults"
+        ));
+        let heap_output =
+            std::fs::read_to_string(path!(output_path | "ctf-test" | "pwn" | "heap.md"))?;
+        assert!(heap_output.ends_with("![solve.png](</ctf-test/pwn/files/solve.png>)"));
+
+        let output_dir = TempDir::new()?;
+        process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                callouts: Some(CalloutStyle::Shortcode),
+                ..Default::default()
+            },
+        )?;
+        let output_path = output_dir.path();
+        let chal_output = std::fs::read_to_string(path!(output_path | "ctf-test" | "example.md"))?;
+        assert!(chal_output.contains("<img src=\"/ctf-test/pwn/files/solve.png\" width=\"300\">"));
+        assert!(chal_output.ends_with(
+            "{% callout(kind=\"warning\", title=\"<mark>careful</mark>\") %}\nit segfaults\n{% end %}"
+        ));
+
+        // zola has no escapes in shortcode arguments, so titles are quoted with what they don't use
+        let zola = backend::Zola::default();
+        let callout = |title| markdown::Callout {
+            kind: "note",
+            title: Some(title),
+            body: "x".to_string(),
+        };
+        assert_eq!(
+            zola.callout_shortcode(&callout("the \"fix\" – 2")),
+            "{% callout(kind=\"note\", title='the \"fix\" – 2') %}\nx\n{% end %}"
+        );
+        assert_eq!(
+            zola.callout_shortcode(&callout("\"it's\" `x`")),
+            "{% callout(kind=\"note\") %}\n**\"it's\" `x`**\n\nx\n{% end %}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn selective_assets() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        let ctf_dir = input_dir.path().join("ctf-test");
+        std::fs::write(
+            ctf_dir.join("meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+
+[assets]
+exclude = [\"core*\"]
+
+[challenges.example]
+name = \"example\"",
+        )?;
+        for dir in [".git", "__pycache__", "files", "build"] {
+            std::fs::create_dir_all(ctf_dir.join(dir))?;
+        }
+        for file in [
+            ".git/config",
+            "__pycache__/solve.pyc",
+            ".DS_Store",
+            "core.1337",
+            "chal.bin",
+            "files/solve.py",
+            "files/notes.txt",
+            "build/out.o",
+        ] {
+            std::fs::write(ctf_dir.join(file), "")?;
+        }
+        std::fs::write(ctf_dir.join(".exportignore"), "*.bin\n")?;
+        std::fs::write(ctf_dir.join(".gitignore"), "build/\n")?;
+
+        let copied = |options: &ExportOptions| -> Result<Vec<String>> {
+            let output_dir = TempDir::new()?;
+            process_input_folder(
+                input_dir.path().as_os_str().to_string_lossy().as_ref(),
+                output_dir.path().as_os_str().to_string_lossy().as_ref(),
+                &backend::Zola::default(),
+                options,
+            )?;
+            let output_path = output_dir.path().join("ctf-test");
+            let mut copied = WalkDir::new(&output_path)
+                .into_iter()
+                .filter_map(std::result::Result::ok)
+                .filter(|x| x.file_type().is_file())
+                .map(|x| x.path().strip_prefix(&output_path).unwrap().to_owned())
+                .map(|x| x.to_string_lossy().replace('\\', "/"))
+                .filter(|x| !x.ends_with(".md"))
+                .collect::<Vec<_>>();
+            copied.sort();
+            Ok(copied)
+        };
+
+        assert_eq!(
+            copied(&ExportOptions::default())?,
+            [
+                "build/out.o",
+                "example_asset",
+                "files/notes.txt",
+                "files/solve.py"
+            ]
+        );
+        assert_eq!(
+            copied(&ExportOptions {
+                assets: AssetGlobs {
+                    include: vec!["files/*".to_string()],
+                    exclude: vec!["*.txt".to_string()],
+                },
+                gitignore: true,
+                ..Default::default()
+            })?,
+            ["files/solve.py"]
+        );
+
+        std::fs::write(
+            ctf_dir.join("example.md"),
+            "[solver](files/solve.py) ![[notes.txt]] [handout](chal.bin)",
+        )?;
+        assert_eq!(
+            copied(&ExportOptions {
+                referenced_assets: true,
+                ..Default::default()
+            })?,
+            ["files/notes.txt", "files/solve.py"]
+        );
+        // the description is on the index page, so what it links to is referenced too
+        std::fs::write(
+            ctf_dir.join("meta.toml"),
+            "name = \"test lol\"
+date = \"2022-01-07\"
+description = \"![cover](cover.png)\"
+
+[assets]
+exclude = [\"core*\"]
+
+[challenges.example]
+name = \"example\"",
+        )?;
+        std::fs::write(ctf_dir.join("cover.png"), "")?;
+        assert_eq!(
+            copied(&ExportOptions {
+                referenced_assets: true,
+                ..Default::default()
+            })?,
+            ["cover.png", "files/notes.txt", "files/solve.py"]
+        );
+        let output_dir = TempDir::new()?;
+        let error = process_input_folder(
+            input_dir.path().as_os_str().to_string_lossy().as_ref(),
+            output_dir.path().as_os_str().to_string_lossy().as_ref(),
+            &backend::Zola::default(),
+            &ExportOptions {
+                strict: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("example.md:1: link to \"chal.bin\", which is excluded from the assets"));
+
+        Ok(())
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ParsedFrontMatter {
+        title: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        taxonomies: Option<ParsedTaxonomies>,
+        #[serde(default, alias = "author")]
+        authors: Vec<String>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ParsedTaxonomies {
+        tags: Vec<String>,
+        authors: Vec<String>,
+    }
+
+    #[test]
+    fn hostile_names_round_trip() -> Result<()> {
+        let input_dir = make_input_dir()?;
+        std::fs::write(
+            input_dir.path().join("ctf-test/meta.toml"),
+            r#"name = "test \"lol\" \\ \n ctf"
+date = "2022-01-07"
+
+[challenges]
+[challenges.example]
+name = "'; DROP TABLE \"\"\" --- +++"
+tags = ["a\"b", "c\\d", "- e: f"]"#,
+        )?;
+        let meta: CTFMeta = toml::from_str(&std::fs::read_to_string(
+            input_dir.path().join("ctf-test/meta.toml"),
+        )?)?;
+        let challenge = &meta.challenges["example"];
+        let authors = vec!["sky \"the\" \\ author".to_string()];
+
+        for backend in [OutputType::Zola, OutputType::Hugo, OutputType::Jekyll] {
+            let output_dir = TempDir::new()?;
+            process_input_folder(
+                input_dir.path().as_os_str().to_string_lossy().as_ref(),
+                output_dir.path().as_os_str().to_string_lossy().as_ref(),
+                backend
+                    .backend(BackendOptions {
+                        zola_authors: ZolaAuthors::Both,
+                        layout: Layout::Page,
+                    })
+                    .as_ref(),
+                &ExportOptions {
+                    authors: authors.clone(),
+                    ..Default::default()
+                },
+            )?;
+            let page_path = WalkDir::new(output_dir.path())
+                .into_iter()
+                .filter_map(|x| x.ok())
+                .find(|x| x.file_name().to_string_lossy().contains("example.md"))
+                .unwrap()
+                .into_path();
+            let page = std::fs::read_to_string(page_path)?;
+
+            let parsed: ParsedFrontMatter = match backend {
+                OutputType::Zola | OutputType::Hugo => {
+                    toml::from_str(page[4..].split("\n+++\n").next().unwrap())?
+                }
+                OutputType::Jekyll => {
+                    serde_yaml::from_str(page[4..].split("\n---\n").next().unwrap())?
+                }
+            };
+            let (tags, parsed_authors) = match parsed.taxonomies {
+                Some(taxonomies) => (taxonomies.tags, taxonomies.authors),
+                None => (parsed.tags, parsed.authors),
+            };
+            assert_eq!(parsed.title, challenge.name);
+            assert_eq!(&tags, challenge.tags.as_ref().unwrap());
+            assert_eq!(parsed_authors, authors);
+        }
+
+        Ok(())
+    }
 }
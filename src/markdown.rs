@@ -0,0 +1,554 @@
+use pulldown_cmark::{Event, HeadingLevel, LinkType, Options, Parser, Tag};
+use regex::{Captures, Regex};
+use std::ops::Range;
+use std::str::FromStr;
+
+// the extensions generators commonly enable, so we see the same structure they will
+fn parser(content: &str) -> Parser<'_, '_> {
+    Parser::new_ext(
+        content,
+        Options::ENABLE_TABLES
+            | Options::ENABLE_FOOTNOTES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS,
+    )
+}
+
+/// pushes every heading in `content` down by `levels`, up to h6
+///
+/// setext headings are rewritten as atx ones since they only go down to h2, anything that isn't
+/// a heading, like `#` lines in code blocks, is left alone
+pub fn demote_headings(content: &str, levels: usize) -> String {
+    if levels == 0 {
+        return content.to_string();
+    }
+
+    let mut demoted = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut events = parser(content).into_offset_iter();
+    while let Some((event, range)) = events.next() {
+        if let Event::Start(Tag::Heading(level, ..)) = event {
+            // the source of the inlines, a setext heading's lines can start with container
+            // markers like `>` which aren't part of its text
+            let mut text = String::new();
+            let mut line_start = None;
+            let mut end = range.start;
+            for (event, inline) in events.by_ref() {
+                match event {
+                    Event::End(Tag::Heading(..)) => break,
+                    Event::SoftBreak => {
+                        if let Some(start) = line_start.take() {
+                            text.push_str(&content[start..inline.start]);
+                            text.push(' ');
+                        }
+                    }
+                    _ => {
+                        line_start.get_or_insert(inline.start);
+                        end = end.max(inline.end);
+                    }
+                }
+            }
+            if let Some(start) = line_start {
+                text.push_str(&content[start..end]);
+            }
+
+            demoted.push_str(&content[last..range.start]);
+            demoted.push_str(&demote_heading(
+                &content[range.clone()],
+                text.trim(),
+                level as usize + levels,
+            ));
+            last = range.end;
+        }
+    }
+    demoted.push_str(&content[last..]);
+    demoted
+}
+
+fn demote_heading(heading: &str, text: &str, level: usize) -> String {
+    let hashes = "#".repeat(level.min(HeadingLevel::H6 as usize));
+    let (heading, newline) = match heading.strip_suffix('\n') {
+        Some(heading) => (heading, "\n"),
+        None => (heading, ""),
+    };
+    if heading.starts_with('#') {
+        return format!("{}{}{}", hashes, heading.trim_start_matches('#'), newline);
+    }
+    // setext: the text, then a line of = or -
+    format!("{} {}{}", hashes, text, newline)
+}
+
+/// text of the first top level heading, without its formatting
+pub fn first_heading(content: &str) -> Option<String> {
+    let mut heading = None;
+    for event in parser(content) {
+        match (event, heading.as_mut()) {
+            (Event::Start(Tag::Heading(HeadingLevel::H1, ..)), None) => {
+                heading = Some(String::new())
+            }
+            (Event::Text(text) | Event::Code(text), Some(heading)) => heading.push_str(&text),
+            (Event::End(Tag::Heading(..)), Some(_)) => break,
+            _ => {}
+        }
+    }
+    heading.map(|x| x.trim().to_string())
+}
+
+/// a url in markdown, as it's written there
+pub struct Link<'a> {
+    pub url: &'a str,
+    /// 1-based line the url is on
+    pub line: usize,
+}
+
+/// replaces each url in `content` with what `rewrite` returns for it, if anything
+///
+/// that's inline links and images, reference definitions and `href`/`src` attributes in html,
+/// not urls in code
+pub fn rewrite_links(content: &str, mut rewrite: impl FnMut(&Link) -> Option<String>) -> String {
+    let mut urls = link_urls(content);
+    urls.sort_unstable_by_key(|x| x.start);
+    urls.dedup();
+
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last = 0;
+    for url in urls {
+        let link = Link {
+            url: &content[url.clone()],
+            line: content[..url.start].matches('\n').count() + 1,
+        };
+        if let Some(replacement) = rewrite(&link) {
+            rewritten.push_str(&content[last..url.start]);
+            rewritten.push_str(&replacement);
+            last = url.end;
+        }
+    }
+    rewritten.push_str(&content[last..]);
+    rewritten
+}
+
+/// puts `prefix` at the start of every absolute path url in `content`, so `/x` becomes
+/// `/prefix/x`
+pub fn prefix_links(content: &str, prefix: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        return content.to_string();
+    }
+    rewrite_links(content, |link| {
+        Some(format!("/{}{}", prefix, link.url)).filter(|_| is_path(link.url))
+    })
+}
+
+/// an obsidian style `[[target]]` link in markdown
+pub struct WikiLink<'a> {
+    pub target: &'a str,
+    /// text after a `|`, shown instead of the target
+    pub alias: Option<&'a str>,
+    /// `![[target]]`, which embeds the target rather than linking to it
+    pub embed: bool,
+    /// 1-based line the link is on
+    pub line: usize,
+}
+
+/// replaces each `[[target]]`, `[[target|alias]]` or `![[target]]` in `content` outside of code
+/// with what `rewrite` returns for it, if anything
+pub fn rewrite_wikilinks(
+    content: &str,
+    mut rewrite: impl FnMut(&WikiLink) -> Option<String>,
+) -> String {
+    let wikilink_regex = Regex::new(r"(!?)\[\[([^\[\]\n|]+)(?:\|([^\[\]\n]*))?\]\]").unwrap();
+    rewrite_text(content, &wikilink_regex, |captures, line| {
+        rewrite(&WikiLink {
+            // obsidian escapes the | in tables
+            target: captures[2].strip_suffix('\\').unwrap_or(&captures[2]),
+            alias: captures.get(3).map(|x| x.as_str()),
+            embed: !captures[1].is_empty(),
+            line,
+        })
+    })
+}
+
+/// replaces obsidian style `==highlights==` outside of code with what `convert` makes of the
+/// highlighted text
+pub fn convert_highlights(content: &str, convert: impl Fn(&str) -> String) -> String {
+    let highlight_regex = Regex::new(r"==([^\s=](?:[^=\n]*[^\s=])?)==").unwrap();
+    rewrite_text(content, &highlight_regex, |captures, _| {
+        Some(convert(&captures[1]))
+    })
+}
+
+// replaces matches of `regex` in text outside of code with what `rewrite` returns for them, which
+// gets the line the match is on
+fn rewrite_text(
+    content: &str,
+    regex: &Regex,
+    mut rewrite: impl FnMut(&Captures, usize) -> Option<String>,
+) -> String {
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last = 0;
+    for text in text_ranges(content) {
+        for captures in regex.captures_iter(&content[text.clone()]) {
+            let whole = captures.get(0).unwrap();
+            let start = text.start + whole.start();
+            let line = content[..start].matches('\n').count() + 1;
+            if let Some(replacement) = rewrite(&captures, line) {
+                rewritten.push_str(&content[last..start]);
+                rewritten.push_str(&replacement);
+                last = text.start + whole.end();
+            }
+        }
+    }
+    rewritten.push_str(&content[last..]);
+    rewritten
+}
+
+/// an obsidian style `> [!kind] title` callout
+pub struct Callout<'a> {
+    /// `note`, `warning` and so on, as written
+    pub kind: &'a str,
+    pub title: Option<&'a str>,
+    /// markdown inside the callout, without the `>`s
+    pub body: String,
+}
+
+impl Callout<'_> {
+    /// the title, or the kind if it has none
+    pub fn title(&self) -> String {
+        match self.title {
+            Some(title) => title.to_string(),
+            None => {
+                let mut kind = self.kind.chars();
+                kind.next()
+                    .map_or(String::new(), |x| x.to_uppercase().chain(kind).collect())
+            }
+        }
+    }
+
+    /// a plain blockquote with the title in bold
+    pub fn quote(&self) -> String {
+        quote(&format!("**{}**\n\n{}", self.title(), self.body))
+    }
+
+    /// a github style `> [!NOTE]` alert, which hugo renders natively
+    pub fn alert(&self) -> String {
+        let body = match self.title {
+            Some(title) => format!("**{}**\n\n{}", title, self.body),
+            None => self.body.clone(),
+        };
+        format!("> [!{}]\n{}", self.kind.to_uppercase(), quote(&body))
+    }
+}
+
+fn quote(content: &str) -> String {
+    content
+        .trim_end()
+        .lines()
+        .map(|x| match x {
+            "" => ">".to_string(),
+            x => format!("> {}", x),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// how callouts are written out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalloutStyle {
+    /// see [`Callout::quote`]
+    Quote,
+    /// see [`Callout::alert`]
+    Alert,
+    /// the generator's shortcode, see `SiteBackend::callout_shortcode`
+    Shortcode,
+}
+
+impl FromStr for CalloutStyle {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "quote" => Ok(CalloutStyle::Quote),
+            "alert" => Ok(CalloutStyle::Alert),
+            "shortcode" => Ok(CalloutStyle::Shortcode),
+            _ => Err("callouts should be \"quote\", \"alert\" or \"shortcode\""),
+        }
+    }
+}
+
+/// replaces each callout in `content` with what `rewrite` makes of it, callouts inside callouts
+/// first
+pub fn rewrite_callouts(content: &str, rewrite: &mut dyn FnMut(&Callout) -> String) -> String {
+    let callout_regex = Regex::new(r"^>[ \t]?\[!([\w-]+)\][+-]?[ \t]*(.*)$").unwrap();
+    let prefix_regex = Regex::new(r"^[ \t]*>[ \t]?").unwrap();
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut depth = 0;
+    for (event, range) in parser(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::BlockQuote) => depth += 1,
+            Event::End(Tag::BlockQuote) => depth -= 1,
+            _ => continue,
+        }
+        if !matches!(event, Event::Start(_)) || depth != 1 {
+            continue;
+        }
+        let quote = &content[range.clone()];
+        let (first_line, rest) = quote.split_once('\n').unwrap_or((quote, ""));
+        let captures = match callout_regex.captures(first_line) {
+            Some(captures) => captures,
+            None => continue,
+        };
+        let body = rest
+            .lines()
+            .map(|x| prefix_regex.replace(x, ""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let callout = Callout {
+            kind: captures.get(1).unwrap().as_str(),
+            title: captures
+                .get(2)
+                .map(|x| x.as_str())
+                .filter(|x| !x.is_empty()),
+            body: rewrite_callouts(&body, rewrite),
+        };
+        // in a list item the lines after the first need the item's indentation to stay in it
+        let line_start = content[..range.start].rfind('\n').map_or(0, |x| x + 1);
+        let indent = content[line_start..range.start]
+            .chars()
+            .map(|x| if x == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let replacement = rewrite(&callout)
+            .lines()
+            .enumerate()
+            .map(|(i, x)| match x {
+                _ if i == 0 || x.is_empty() => x.to_string(),
+                x => format!("{}{}", indent, x),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        rewritten.push_str(&content[last..range.start]);
+        rewritten.push_str(&replacement);
+        if quote.ends_with('\n') {
+            rewritten.push('\n');
+        }
+        last = range.end;
+    }
+    rewritten.push_str(&content[last..]);
+    rewritten
+}
+
+// runs of plain text in `content`, outside of code and html
+fn text_ranges(content: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    let mut in_code_block = false;
+    for (event, range) in parser(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            // brackets and escapes come out as text of their own, so runs of text are joined
+            // back up, over the backslash of escapes
+            Event::Text(_) if !in_code_block => match ranges.last_mut() {
+                Some(last)
+                    if content[last.end..range.start]
+                        .trim_start_matches('\\')
+                        .is_empty() =>
+                {
+                    last.end = range.end
+                }
+                _ => ranges.push(range),
+            },
+            _ => {}
+        }
+    }
+    ranges
+}
+
+// a path on the same host, not a url with a scheme or a protocol relative one
+fn is_path(url: &str) -> bool {
+    url.starts_with('/') && !url.starts_with("//")
+}
+
+// where the urls in `content` are written
+fn link_urls(content: &str) -> Vec<Range<usize>> {
+    let mut urls = vec![];
+    let mut parser = parser(content).into_offset_iter();
+    for (_, def) in parser.reference_definitions().iter() {
+        let label_end = content[def.span.clone()].find("]:").unwrap() + "]:".len();
+        urls.extend(url_at(content, def.span.start + label_end));
+    }
+
+    // where the text of each link being walked has got to, the url comes after it
+    let mut text_ends: Vec<usize> = vec![];
+    let attribute_regex =
+        Regex::new(r#"(?i)\b(?:href|src)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap();
+    for (event, range) in parser.by_ref() {
+        match event {
+            Event::Start(Tag::Link(..)) => {
+                text_ends.push(range.start + "[".len());
+                continue;
+            }
+            Event::Start(Tag::Image(..)) => {
+                text_ends.push(range.start + "![".len());
+                continue;
+            }
+            Event::End(Tag::Link(link_type, ..) | Tag::Image(link_type, ..)) => {
+                let text_end = text_ends.pop().unwrap();
+                if link_type == LinkType::Inline && content[text_end..].starts_with("](") {
+                    urls.extend(url_at(content, text_end + "](".len()));
+                }
+            }
+            Event::Html(_) => urls.extend(
+                attribute_regex
+                    .captures_iter(&content[range.clone()])
+                    .filter_map(|x| x.iter().skip(1).flatten().next())
+                    .map(|x| range.start + x.start()..range.start + x.end()),
+            ),
+            _ => {}
+        }
+        if let Some(text_end) = text_ends.last_mut() {
+            *text_end = (*text_end).max(range.end);
+        }
+    }
+    urls
+}
+
+// the url starting after any whitespace from `start`, inside <> or up to whitespace or an
+// unbalanced )
+fn url_at(content: &str, start: usize) -> Option<Range<usize>> {
+    let rest = &content[start..];
+    let start = start + rest.len() - rest.trim_start().len();
+    let rest = &content[start..];
+    if let Some(url) = rest.strip_prefix('<') {
+        let end = url.find(['>', '\n'])?;
+        return Some(start + 1..start + 1 + end);
+    }
+
+    let mut depth = 0;
+    let mut escaped = false;
+    let end = rest
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '(' => depth += 1,
+                ')' if depth == 0 => return true,
+                ')' => depth -= 1,
+                _ if c.is_whitespace() => return true,
+                _ => {}
+            }
+            false
+        })
+        .map_or(rest.len(), |(i, _)| i);
+    Some(start..start + end).filter(|x| !x.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn headings() {
+        assert_eq!(
+            demote_headings("# a\ntext\n## b ##\n#not a heading\n", 1),
+            "## a\ntext\n### b ##\n#not a heading\n"
+        );
+        assert_eq!(
+            demote_headings("Setext\nheading\n===\n\nSub\n---", 2),
+            "### Setext heading\n\n#### Sub"
+        );
+        assert_eq!(
+            demote_headings("> a\n> *b\\*\n> ===\n\n- c\n  d\n  ---\n", 1),
+            "> ## a *b\\*\n\n- ### c d\n"
+        );
+        assert_eq!(
+            demote_headings("##### a\n###### b\n", 2),
+            "###### a\n###### b\n"
+        );
+        assert_eq!(
+            demote_headings("```sh\n# comment\n```\n\n    # indented\n", 1),
+            "```sh\n# comment\n```\n\n    # indented\n"
+        );
+
+        assert_eq!(
+            first_heading("#pwn #heap\n```c\n#include <stdio.h>\n```\n## Sub\n# Baby `heap` *2*\n"),
+            Some("Baby heap 2".to_string())
+        );
+        assert_eq!(first_heading("Setext\n===\n"), Some("Setext".to_string()));
+        assert_eq!(first_heading("#include <stdio.h>\n"), None);
+    }
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            prefix_links(
+                "[a [b] c](/x \"t\") ![i](</y z>) [![i](/a.png)](/b)",
+                "/blog/"
+            ),
+            "[a [b] c](/blog/x \"t\") ![i](</blog/y z>) [![i](/blog/a.png)](/blog/b)"
+        );
+        assert_eq!(
+            prefix_links(
+                "[r][1] [](https://e.com) [](//e.com) [](rel)\n\n[1]: /ref",
+                "blog"
+            ),
+            "[r][1] [](https://e.com) [](//e.com) [](rel)\n\n[1]: /blog/ref"
+        );
+        assert_eq!(
+            prefix_links("<a href=\"/h\">x</a>\n\n<img src='/s.png'>\n", "blog"),
+            "<a href=\"/blog/h\">x</a>\n\n<img src='/blog/s.png'>\n"
+        );
+        assert_eq!(
+            prefix_links("```\n[x](/code)\n```\n`[x](/code)`", "blog"),
+            "```\n[x](/code)\n```\n`[x](/code)`"
+        );
+    }
+
+    #[test]
+    fn wikilinks() {
+        let rewrite = |content| {
+            rewrite_wikilinks(content, |link| {
+                Some(format!("<{}@{}>", link.target, link.line)).filter(|_| link.target != "keep")
+            })
+        };
+        assert_eq!(
+            rewrite("see [[ctf/a]] and [[keep]]\n\n*[[b]]*"),
+            "see <ctf/a@1> and [[keep]]\n\n*<b@3>*"
+        );
+        assert_eq!(
+            rewrite("`[[a]]`\n\n```\n[[a]]\n```\n\\[\\[a]]"),
+            "`[[a]]`\n\n```\n[[a]]\n```\n\\[\\[a]]"
+        );
+    }
+
+    #[test]
+    fn obsidian_syntax() {
+        assert_eq!(
+            rewrite_wikilinks("![[a b.png|300]] [[note#h\\|alias]]", |link| {
+                Some(format!("{}:{}:{:?}", link.embed, link.target, link.alias))
+            }),
+            "true:a b.png:Some(\"300\") false:note#h:Some(\"alias\")"
+        );
+        assert_eq!(
+            convert_highlights("==a b== a == b == c\n`==c==`", |x| format!(
+                "<mark>{}</mark>",
+                x
+            )),
+            "<mark>a b</mark> a == b == c\n`==c==`"
+        );
+
+        let content = "> [!tip] Use *this*\n> body\n>\n> > [!warning]\n> > inner\n\n> plain\n";
+        assert_eq!(
+            rewrite_callouts(content, &mut |x| x.quote()),
+            "> **Use *this***\n>\n> body\n>\n> > **Warning**\n> >\n> > inner\n\n> plain\n"
+        );
+        assert_eq!(
+            rewrite_callouts("> [!note]\n> body", &mut |x| x.alert()),
+            "> [!NOTE]\n> body"
+        );
+        assert_eq!(
+            rewrite_callouts("- > [!note] t\n  > body\n- next\n", &mut |x| x.quote()),
+            "- > **t**\n  >\n  > body\n- next\n"
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use crate::assets;
+use crate::diagnostics::Diagnostics;
+use crate::markdown;
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::value::{Datetime, Table};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CTFMeta {
+    pub name: String,
+    pub date: Date,
+    pub description: Option<String>,
+    /// tags of the ctf itself, see [`CTFMeta::tags`]
+    pub tags: Option<Vec<String>>,
+    /// front matter template for this ctf, relative to the ctf folder
+    pub front_matter_template: Option<PathBuf>,
+    /// which assets to copy, on top of those given on the command line
+    #[serde(default)]
+    pub assets: AssetGlobs,
+    /// in the order they're written in meta.toml
+    pub challenges: IndexMap<String, ChallengeMeta>,
+    /// any other keys, copied into the front matter of the index page
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+impl CTFMeta {
+    /// the ctf's tags, `ctf-writeups` if it doesn't have any
+    pub fn tags(&self) -> Vec<String> {
+        self.tags
+            .clone()
+            .unwrap_or_else(|| vec!["ctf-writeups".to_string()])
+    }
+
+    /// builds the meta of a ctf folder without a meta.toml from the markdown in it
+    ///
+    /// every markdown file is a challenge, described by its yaml front matter, falling back to
+    /// its first heading for the name. files with front matter that can't describe a challenge
+    /// are reported and skipped. the ctf is named after the folder and dated by its earliest
+    /// challenge. `None` if there's no markdown, so the folder isn't a ctf at all
+    pub fn infer(
+        ctf_folder: &Path,
+        folder_name: &str,
+        assets: &AssetGlobs,
+        gitignore: bool,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Option<CTFMeta>, String> {
+        let mut challenges = IndexMap::new();
+        let markdown = markdown_files(ctf_folder, assets, gitignore).map_err(|e| e.to_string())?;
+        for (key, path) in markdown {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+            let (front_matter, body) = split_front_matter(&content);
+            match infer_challenge(&key, front_matter, body) {
+                Ok(challenge) => {
+                    challenges.insert(key, challenge);
+                }
+                Err(e) => diagnostics.report(
+                    folder_name,
+                    &path,
+                    format!("bad front matter, skipping it: {}", e),
+                ),
+            }
+        }
+        if challenges.is_empty() {
+            return Ok(None);
+        }
+
+        let date = challenges
+            .values()
+            .filter_map(|x: &ChallengeMeta| x.date.clone())
+            .min_by_key(|x| x.to_string())
+            .ok_or("no meta.toml and no challenge has a date in its front matter")?;
+        Ok(Some(CTFMeta {
+            name: folder_name.to_string(),
+            date,
+            description: None,
+            tags: None,
+            front_matter_template: None,
+            assets: AssetGlobs::default(),
+            challenges,
+            extra: Table::new(),
+        }))
+    }
+}
+
+// the challenge described by the front matter and body of a markdown file
+fn infer_challenge(
+    key: &str,
+    front_matter: Option<&str>,
+    body: &str,
+) -> Result<ChallengeMeta, serde_yaml::Error> {
+    let mut fields = match front_matter.filter(|x| !x.trim().is_empty()) {
+        Some(front_matter) => serde_yaml::from_str(front_matter)?,
+        None => serde_yaml::Mapping::new(),
+    };
+    // obsidian leaves empty properties like `aliases:` around, which toml has no value for
+    drop_nulls(&mut fields);
+    if let Some(title) = fields.remove("title") {
+        fields.entry("name".into()).or_insert(title);
+    }
+    if !fields.contains_key("name") {
+        let name = markdown::first_heading(body).unwrap_or_else(|| key.to_string());
+        fields.insert("name".into(), name.into());
+    }
+    // and takes `tags: heap` or `tags: heap, pwn` for a list
+    if let Some(serde_yaml::Value::String(tags)) = fields.get("tags") {
+        let tags = tags
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(serde_yaml::Value::from)
+            .collect();
+        fields.insert("tags".into(), tags);
+    }
+    serde_yaml::from_value(serde_yaml::Value::Mapping(fields))
+}
+
+fn drop_nulls(mapping: &mut serde_yaml::Mapping) {
+    mapping.retain(|_, value| !value.is_null());
+    for value in mapping.values_mut() {
+        match value {
+            serde_yaml::Value::Mapping(x) => drop_nulls(x),
+            serde_yaml::Value::Sequence(x) => x.retain(|x| !x.is_null()),
+            _ => {}
+        }
+    }
+}
+
+/// gitignore style globs picking which files of a ctf folder are copied as assets
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AssetGlobs {
+    /// only files matching one of these are copied, if there are any
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// files matching these aren't copied
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl AssetGlobs {
+    /// the globs of both
+    pub fn merge(&self, other: &AssetGlobs) -> AssetGlobs {
+        AssetGlobs {
+            include: self.include.iter().chain(&other.include).cloned().collect(),
+            exclude: self.exclude.iter().chain(&other.exclude).cloned().collect(),
+        }
+    }
+}
+
+/// markdown files under a ctf folder, keyed like challenges in meta.toml (path without `.md`)
+///
+/// skips what `globs` and ignore files exclude, see `assets::markdown`
+pub fn markdown_files(
+    ctf_folder: &Path,
+    globs: &AssetGlobs,
+    gitignore: bool,
+) -> color_eyre::Result<Vec<(String, PathBuf)>> {
+    Ok(assets::markdown(ctf_folder, globs, gitignore)?
+        .into_iter()
+        .filter_map(|x| {
+            let key = x
+                .strip_prefix(ctf_folder)
+                .ok()?
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((key, x))
+        })
+        .collect())
+}
+
+/// splits a leading `---` delimited yaml header off some markdown
+pub fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let rest = match content.strip_prefix("---\n") {
+        Some(rest) => rest,
+        None => return (None, content),
+    };
+    if let Some(body) = rest.strip_prefix("---\n") {
+        return (Some(""), body);
+    }
+    match rest.find("\n---\n") {
+        Some(end) => (Some(&rest[..end]), &rest[end + 5..]),
+        None => match rest.strip_suffix("\n---") {
+            Some(front_matter) => (Some(front_matter), ""),
+            None => (None, content),
+        },
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChallengeMeta {
+    pub name: String,
+    pub tags: Option<Vec<String>>,
+    /// position when sorting by weight, lowest first
+    #[serde(alias = "order")]
+    pub weight: Option<i64>,
+    #[serde(flatten)]
+    pub info: ChallengeInfo,
+    /// overrides the ctf date for this challenge
+    pub date: Option<Date>,
+    /// overrides the authors given on the command line for this challenge
+    pub authors: Option<Vec<String>>,
+    pub updated: Option<Date>,
+    /// url slug of the challenge page, overrides the slug policy
+    pub slug: Option<String>,
+    /// any other keys, copied into the front matter of the challenge page
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+/// order of the challenges of a ctf, on the index page and in `weight` front matter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    /// by `weight`, challenges without one after those with one
+    Weight,
+    Name,
+    Category,
+    /// most points first
+    Points,
+    Date,
+}
+
+impl std::str::FromStr for SortKey {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "weight" => Ok(SortKey::Weight),
+            "name" => Ok(SortKey::Name),
+            "category" => Ok(SortKey::Category),
+            "points" => Ok(SortKey::Points),
+            "date" => Ok(SortKey::Date),
+            _ => {
+                Err("sort key should be \"weight\", \"name\", \"category\", \"points\" or \"date\"")
+            }
+        }
+    }
+}
+
+// Some before None, so unset values sort last
+fn some_first<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    }
+}
+
+impl SortKey {
+    /// sorts the challenges of `ctf`, ties stay in meta.toml order
+    pub fn sort<'a>(&self, ctf: &'a CTFMeta) -> Vec<(&'a String, &'a ChallengeMeta)> {
+        let mut challenges = ctf.challenges.iter().collect::<Vec<_>>();
+        challenges.sort_by(|(_, a), (_, b)| match self {
+            SortKey::Weight => some_first(a.weight, b.weight),
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Category => some_first(a.info.category.as_ref(), b.info.category.as_ref()),
+            SortKey::Points => some_first(a.info.points.map(Reverse), b.info.points.map(Reverse)),
+            SortKey::Date => {
+                let a = a.date.as_ref().unwrap_or(&ctf.date).to_string();
+                let b = b.date.as_ref().unwrap_or(&ctf.date).to_string();
+                a.cmp(&b)
+            }
+        });
+        challenges
+    }
+}
+
+/// how the url slug of a challenge page, and so its file name, is picked when meta.toml doesn't
+/// set one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlugPolicy {
+    /// the key, slugified
+    Slugify,
+    /// the key as is
+    Key,
+    /// the challenge name, slugified
+    Name,
+}
+
+impl std::str::FromStr for SlugPolicy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slugify" => Ok(SlugPolicy::Slugify),
+            "key" => Ok(SlugPolicy::Key),
+            "name" => Ok(SlugPolicy::Name),
+            _ => Err("slug should be \"slugify\", \"key\" or \"name\""),
+        }
+    }
+}
+
+impl SlugPolicy {
+    /// slug of the challenge at `key`, keeping the folders of keys in subfolders
+    pub fn slug(&self, key: &str, challenge: &ChallengeMeta) -> String {
+        let (folder, file) = match key.rsplit_once('/') {
+            Some((folder, file)) => (Some(folder), file),
+            None => (None, key),
+        };
+        let file = match (&challenge.slug, self) {
+            (Some(slug), _) => slug.clone(),
+            (None, SlugPolicy::Slugify) => slug::slugify(file),
+            (None, SlugPolicy::Key) => file.to_string(),
+            (None, SlugPolicy::Name) => slug::slugify(&challenge.name),
+        };
+        match (folder, self) {
+            (Some(folder), SlugPolicy::Key) => format!("{}/{}", folder, file),
+            (Some(folder), _) => {
+                let folder = folder.split('/').map(slug::slugify).collect::<Vec<_>>();
+                format!("{}/{}", folder.join("/"), file)
+            }
+            (None, _) => file,
+        }
+    }
+}
+
+/// what the ctf itself had to say about a challenge
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChallengeInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub points: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solves: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flag: Option<String>,
+}
+
+impl ChallengeInfo {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        [
+            ("category", self.category.clone()),
+            ("points", self.points.map(|x| x.to_string())),
+            ("solves", self.solves.map(|x| x.to_string())),
+            ("difficulty", self.difficulty.clone()),
+            ("flag", self.flag.as_ref().map(|x| format!("`{}`", x))),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields().is_empty()
+    }
+
+    /// markdown table of whatever is set, for the top of a challenge
+    pub fn info_box(&self) -> Option<String> {
+        let fields = self.fields();
+        if fields.is_empty() {
+            return None;
+        }
+        let row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        Some(
+            row(fields.iter().map(|(name, _)| name.to_string()).collect())
+                + &row(fields.iter().map(|_| "---".to_string()).collect())
+                + &row(fields
+                    .iter()
+                    .map(|(_, value)| value.replace('|', "\\|"))
+                    .collect()),
+        )
+    }
+}
+
+/// a `YYYY-MM-DD` date or RFC 3339 datetime, written either as a toml datetime or a string
+#[derive(Debug, Clone, PartialEq)]
+pub struct Date(Datetime);
+
+impl Date {
+    /// the `YYYY-MM-DD` part
+    pub fn day(&self) -> String {
+        self.0.to_string()[..10].to_string()
+    }
+
+    pub fn datetime(&self) -> &Datetime {
+        &self.0
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid date {:?}, expected YYYY-MM-DD or an RFC 3339 datetime",
+                s
+            )
+        };
+        // toml also accepts bare times, and is happy with a space instead of the T
+        let bytes = s.as_bytes();
+        let date_shaped = bytes.len() >= 10
+            && bytes[..10].iter().enumerate().all(|(i, c)| {
+                if i == 4 || i == 7 {
+                    *c == b'-'
+                } else {
+                    c.is_ascii_digit()
+                }
+            })
+            && (bytes.len() == 10 || bytes[10] == b'T');
+        if !date_shaped {
+            return Err(invalid());
+        }
+        s.parse().map(Date).map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match toml::Value::deserialize(deserializer)? {
+            toml::Value::String(x) => x.parse().map_err(serde::de::Error::custom),
+            toml::Value::Datetime(x) => x.to_string().parse().map_err(serde::de::Error::custom),
+            x => Err(serde::de::Error::custom(format!(
+                "invalid date {}, expected YYYY-MM-DD or an RFC 3339 datetime",
+                x
+            ))),
+        }
+    }
+}
+
+// written as a plain string so templates don't see toml's datetime wire format
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        for good in [
+            "2022-01-07",
+            "2022-01-07T10:00:00",
+            "2022-01-07T10:00:00Z",
+            "2022-01-07T10:00:00+02:00",
+        ] {
+            assert_eq!(good.parse::<Date>().unwrap().to_string(), good);
+        }
+        for bad in [
+            "2022-1-7",
+            "Jan 7 2022",
+            "10:00:00",
+            "2022-13-07",
+            "2022-01-07 10:00:00",
+            "",
+        ] {
+            assert!(bad.parse::<Date>().is_err(), "{:?} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn toml_and_string_dates() {
+        let meta: CTFMeta =
+            toml::from_str("name = \"a\"\ndate = 2022-01-07\n[challenges]").unwrap();
+        assert_eq!(meta.date.day(), "2022-01-07");
+        let meta: CTFMeta =
+            toml::from_str("name = \"a\"\ndate = \"2022-01-07T10:00:00Z\"\n[challenges]").unwrap();
+        assert_eq!(meta.date.day(), "2022-01-07");
+        let err = toml::from_str::<CTFMeta>("name = \"a\"\ndate = \"2022-1-7\"\n[challenges]")
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid date \"2022-1-7\""));
+    }
+
+    #[test]
+    fn slug_policies() {
+        let challenge = ChallengeMeta {
+            name: "Baby's First Pwn".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            SlugPolicy::Slugify.slug("Pwn/Baby 1", &challenge),
+            "pwn/baby-1"
+        );
+        assert_eq!(SlugPolicy::Key.slug("Pwn/Baby 1", &challenge), "Pwn/Baby 1");
+        assert_eq!(
+            SlugPolicy::Name.slug("Pwn/Baby 1", &challenge),
+            "pwn/baby-s-first-pwn"
+        );
+        let challenge = ChallengeMeta {
+            slug: Some("baby".to_string()),
+            ..challenge
+        };
+        assert_eq!(SlugPolicy::Name.slug("Baby 1", &challenge), "baby");
+    }
+
+    #[test]
+    fn sort_keys() {
+        let meta: CTFMeta = toml::from_str(
+            "name = \"a\"
+date = 2022-01-07
+[challenges.zeta]
+name = \"Zeta\"
+category = \"web\"
+points = 100
+[challenges.alpha]
+name = \"alpha\"
+weight = 2
+date = 2022-01-05
+[challenges.mid]
+name = \"Mid\"
+category = \"pwn\"
+points = 300
+weight = 1",
+        )
+        .unwrap();
+        let order = |key: SortKey| {
+            key.sort(&meta)
+                .into_iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(SortKey::Weight), ["mid", "alpha", "zeta"]);
+        assert_eq!(order(SortKey::Name), ["alpha", "mid", "zeta"]);
+        assert_eq!(order(SortKey::Category), ["mid", "zeta", "alpha"]);
+        assert_eq!(order(SortKey::Points), ["mid", "zeta", "alpha"]);
+        assert_eq!(order(SortKey::Date), ["alpha", "zeta", "mid"]);
+    }
+}
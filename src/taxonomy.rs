@@ -0,0 +1,127 @@
+use crate::meta::{CTFMeta, ChallengeMeta};
+use indexmap::IndexMap;
+use std::str::FromStr;
+
+/// taxonomy name to the terms a page is filed under
+pub type Taxonomies = IndexMap<String, Vec<String>>;
+
+// single value keys some backend puts at the top level of the front matter, where hugo and jekyll
+// keep taxonomies too
+const RESERVED_NAMES: &[&str] = &[
+    "title",
+    "date",
+    "updated",
+    "lastmod",
+    "last_modified_at",
+    "weight",
+    "slug",
+    "layout",
+    "points",
+    "solves",
+    "flag",
+];
+
+/// where the terms of a taxonomy come from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaxonomySource {
+    /// the page's own tags: the challenge's tags, or the ctf's tags on the index page
+    Tags,
+    /// the ctf's tags, on every page of the ctf
+    CtfTags,
+    /// the ctf name
+    Ctf,
+    /// the challenge category, nothing on the index page
+    Category,
+}
+
+impl FromStr for TaxonomySource {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "tags" => Ok(TaxonomySource::Tags),
+            "ctf-tags" => Ok(TaxonomySource::CtfTags),
+            "ctf" => Ok(TaxonomySource::Ctf),
+            "category" => Ok(TaxonomySource::Category),
+            _ => Err(format!(
+                "unknown taxonomy source {:?}, should be \"tags\", \"ctf-tags\", \"ctf\" or \"category\"",
+                s
+            )),
+        }
+    }
+}
+
+/// a taxonomy and the sources its terms are collected from, written `name=source,source`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxonomyMapping {
+    pub name: String,
+    pub sources: Vec<TaxonomySource>,
+}
+
+impl TaxonomyMapping {
+    /// the mapping used when none are configured, which files a page's tags under `tags`
+    pub fn defaults() -> Vec<TaxonomyMapping> {
+        vec![TaxonomyMapping {
+            name: "tags".to_string(),
+            sources: vec![TaxonomySource::Tags],
+        }]
+    }
+}
+
+impl FromStr for TaxonomyMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, sources) = s
+            .split_once('=')
+            .ok_or_else(|| format!("taxonomy {:?} should look like name=source,source", s))?;
+        let name = name.trim();
+        if RESERVED_NAMES.contains(&name) {
+            return Err(format!(
+                "taxonomy {:?} would clash with the front matter key of that name",
+                name
+            ));
+        }
+        Ok(TaxonomyMapping {
+            name: name.to_string(),
+            sources: sources
+                .split(',')
+                .map(|x| x.trim().parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// collects the terms of every configured taxonomy for a page, `challenge` is unset for the index
+pub fn resolve(
+    mappings: &[TaxonomyMapping],
+    ctf: &CTFMeta,
+    challenge: Option<&ChallengeMeta>,
+) -> Taxonomies {
+    let ctf_tags = ctf.tags();
+    let mut taxonomies = Taxonomies::new();
+    for mapping in mappings {
+        let terms = taxonomies
+            .entry(mapping.name.clone())
+            .or_insert_with(Vec::new);
+        for source in &mapping.sources {
+            let values: Vec<String> = match (source, challenge) {
+                (TaxonomySource::Tags, Some(challenge)) => {
+                    challenge.tags.clone().unwrap_or_default()
+                }
+                (TaxonomySource::Tags, None) | (TaxonomySource::CtfTags, _) => ctf_tags.clone(),
+                (TaxonomySource::Ctf, _) => vec![ctf.name.clone()],
+                (TaxonomySource::Category, challenge) => challenge
+                    .and_then(|x| x.info.category.clone())
+                    .into_iter()
+                    .collect(),
+            };
+            for value in values {
+                if !terms.contains(&value) {
+                    terms.push(value);
+                }
+            }
+        }
+    }
+    taxonomies
+}
@@ -0,0 +1,35 @@
+use crate::backend::FrontMatter;
+use color_eyre::eyre::{Result, WrapErr};
+use std::path::Path;
+use tera::{Context, Tera};
+
+const TEMPLATE_NAME: &str = "front_matter";
+
+/// user supplied tera template which replaces the front matter a backend would generate
+///
+/// the template gets `title`, `date`, `updated`, `taxonomies` and `authors` as the backend would
+/// have used them, plus the raw `ctf` meta and `challenge` meta (unset on the ctf index page),
+/// and has to emit the delimiters itself
+pub struct FrontMatterTemplate {
+    tera: Tera,
+}
+
+impl FrontMatterTemplate {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("couldn't read front matter template {}", path.display()))?;
+        let mut tera = Tera::default();
+        tera.add_raw_template(TEMPLATE_NAME, &source)
+            .wrap_err_with(|| format!("couldn't parse front matter template {}", path.display()))?;
+        Ok(FrontMatterTemplate { tera })
+    }
+
+    pub fn render(&self, page: &FrontMatter) -> Result<String> {
+        let rendered = self
+            .tera
+            .render(TEMPLATE_NAME, &Context::from_serialize(page)?)
+            .wrap_err_with(|| format!("couldn't render front matter for {:?}", page.title))?;
+        // match the spacing between front matter and content the backends use
+        Ok(rendered.trim_end().to_string() + "\n\n\n")
+    }
+}